@@ -0,0 +1,8 @@
+fn main() {
+    /* exposed as the `BUILD_TIME` predefined constant, see README.md */
+    let build_time = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    println!("cargo:rustc-env=VERMAN_BUILD_TIME={}", build_time);
+}
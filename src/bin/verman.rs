@@ -0,0 +1,101 @@
+use clap::{Parser, Subcommand};
+
+use verman_schema_rs::errors::{SuccessOrVermanSchemaError, VermanSchemaError};
+use verman_schema_rs::json_schema::{json_schema, SchemaKind};
+use verman_schema_rs::loader::loader::Format;
+use verman_schema_rs::models::Pipeline;
+use verman_schema_rs::pipeline::pipeline::ProcessOptions;
+
+/// Run, validate and inspect verMan pipelines (JSON, TOML or YAML)
+#[derive(Parser)]
+#[command(name = "verman", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Subcommands,
+}
+
+#[derive(Subcommand)]
+enum Subcommands {
+    /// Run a `Pipeline` and print the final `CommonContent`
+    Run {
+        file: std::path::PathBuf,
+
+        /// Only run pure commands, e.g., `Echo`; the others report what they would do
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Check a `Pipeline` without running it
+    Validate { file: std::path::PathBuf },
+
+    /// Show the merged env each task would start with
+    Env { file: std::path::PathBuf },
+
+    /// Rewrite a `Pipeline` in another format
+    Convert {
+        file: std::path::PathBuf,
+
+        /// `json` | `toml` | `yaml`
+        #[arg(long, value_parser = parse_format)]
+        to: Format,
+
+        /// Write here instead of stdout
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+
+    /// Print the JSON Schema of a `pipeline` | `task` | `command` | `root`
+    Schema {
+        #[arg(value_parser = parse_schema_kind)]
+        kind: SchemaKind,
+    },
+}
+
+fn parse_format(s: &str) -> Result<Format, String> {
+    s.parse().map_err(|e: VermanSchemaError| e.to_string())
+}
+
+fn parse_schema_kind(s: &str) -> Result<SchemaKind, String> {
+    s.parse().map_err(|e: VermanSchemaError| e.to_string())
+}
+
+async fn run(cli: Cli) -> Result<(), VermanSchemaError> {
+    match cli.command {
+        Subcommands::Run { file, dry_run } => {
+            let common = Pipeline::from_path(file)?
+                .process_with(&ProcessOptions { dry_run })
+                .await?;
+            println!("{}", serde_json::to_string_pretty(&common)?);
+        }
+        Subcommands::Validate { file } => {
+            Pipeline::from_path(&file)?.validate()?;
+            println!("{} is valid", file.display());
+        }
+        Subcommands::Env { file } => {
+            for (task_name, env) in Pipeline::from_path(file)?.task_envs()? {
+                println!("# {}", task_name);
+                env.iter()
+                    .for_each(|(k, v)| println!("{}={}", k, serde_json::to_string(v).unwrap()));
+            }
+        }
+        Subcommands::Convert { file, to, output } => {
+            let s = Pipeline::from_path(file)?.to_string_with_format(to)?;
+            match output {
+                Some(path) => std::fs::write(path, s)?,
+                None => print!("{}", s),
+            }
+        }
+        Subcommands::Schema { kind } => {
+            println!("{}", serde_json::to_string_pretty(&json_schema(kind))?);
+        }
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> SuccessOrVermanSchemaError<std::process::ExitCode> {
+    run(Cli::parse())
+        .await
+        .map(|_| std::process::ExitCode::SUCCESS)
+        .into()
+}
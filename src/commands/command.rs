@@ -1,12 +1,24 @@
+use crate::commands::http_client::{prepare_request, PreparedRequest};
+use crate::commands::jaq::interpolated_filter;
+use crate::commands::jaq::jaq_utils::vars_filter_from_code;
+use crate::commands::script::{parse_shebang, Shebang};
 use crate::commands::shared::merge_env;
 use crate::errors::VermanSchemaError;
-use crate::models::{CommonContent, HttpCommandArgs};
+#[cfg(feature = "wasm")]
+use crate::models::WasmCommandArgs;
+use crate::models::{CommonContent, ExecCommandArgs, HttpCommandArgs};
+use crate::pipeline::pipeline::ProcessOptions;
 
 #[derive(derive_more::Display)]
 pub enum CommandKey {
     #[display("CMD_CURRENT_CONTENT")]
     CurrentContent,
 
+    /// Error of the previous `Command`, when it failed with `continue_on_error`; removed once a
+    /// `Command` succeeds
+    #[display("CMD_LAST_ERROR")]
+    LastError,
+
     #[display("CMD_PREVIOUS_CONTENT")]
     PreviousContent,
 
@@ -14,15 +26,28 @@ pub enum CommandKey {
     PreviousType,
 }
 
-#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Debug, PartialEq, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
 #[serde(tag = "cmd")]
 pub enum Command {
     Echo(CommonContent),
     Env(CommonContent),
+    /// Local program, see `commands::exec::exec`
+    Exec(ExecCommandArgs),
     HttpClient(HttpCommandArgs),
     Interpolate(CommonContent),
     Jaq(CommonContent),
+    /// JavaScript, see `commands::js::js`
+    #[cfg(feature = "js")]
+    Js(CommonContent),
+    #[cfg(feature = "lua")]
+    Lua(CommonContent),
+    #[cfg(feature = "python")]
+    Python(CommonContent),
+    /// Interpreter picked by the shebang on the first line of `content`, e.g., `#!/jq`
+    Script(CommonContent),
     SetEnv(CommonContent),
+    #[cfg(feature = "wasm")]
+    Wasm(WasmCommandArgs),
 }
 
 impl Default for Command {
@@ -33,9 +58,22 @@ impl Default for Command {
 
 impl Command {
     pub async fn process(
+        &self,
+        shared_env_for_cmds: &mut indexmap::IndexMap<String, serde_json::Value>,
+    ) -> Result<CommonContent, VermanSchemaError> {
+        self.process_with(shared_env_for_cmds, &ProcessOptions::default())
+            .await
+    }
+
+    /// Like `Command::process`; with `ProcessOptions.dry_run` only pure `Command`s run
+    pub async fn process_with(
         &self,
         mut shared_env_for_cmds: &mut indexmap::IndexMap<String, serde_json::Value>,
+        options: &ProcessOptions,
     ) -> Result<CommonContent, VermanSchemaError> {
+        if options.dry_run && !self.is_pure() {
+            return self.dry_run(shared_env_for_cmds);
+        }
         match self {
             Command::Echo(ref arg) => crate::commands::echo::echo(&CommonContent {
                 env: {
@@ -43,6 +81,7 @@ impl Command {
                     Some(shared_env_for_cmds.clone())
                 },
                 content: arg.content.to_owned(),
+                ..CommonContent::default()
             }),
             Command::Env(ref arg) => crate::commands::env::env(&CommonContent {
                 env: {
@@ -50,7 +89,22 @@ impl Command {
                     Some(shared_env_for_cmds.clone())
                 },
                 content: arg.content.to_owned(),
+                ..CommonContent::default()
             }),
+            Command::Exec(ref arg) => {
+                crate::commands::exec::exec(&ExecCommandArgs {
+                    common_content: CommonContent {
+                        env: {
+                            merge_env(&mut shared_env_for_cmds, &arg.common_content.env);
+                            Some(shared_env_for_cmds.clone())
+                        },
+                        content: arg.common_content.content.to_owned(),
+                        ..CommonContent::default()
+                    },
+                    ..arg.to_owned()
+                })
+                .await
+            }
             Command::HttpClient(ref arg) => crate::commands::http_client::http(&HttpCommandArgs {
                 args: arg.args.to_owned(),
                 common_content: CommonContent {
@@ -59,9 +113,9 @@ impl Command {
                         Some(shared_env_for_cmds.clone())
                     },
                     content: arg.common_content.content.to_owned(),
+                    ..CommonContent::default()
                 },
-                expectation: arg.expectation.to_owned(),
-                deserialize_to: arg.deserialize_to.to_owned(),
+                ..arg.to_owned()
             })
             .await
             .map(|(_, c)| c),
@@ -72,6 +126,7 @@ impl Command {
                         Some(shared_env_for_cmds.clone())
                     },
                     content: arg.content.to_owned(),
+                    ..CommonContent::default()
                 })
             }
             Command::Jaq(ref arg) => crate::commands::jaq::jaq(&CommonContent {
@@ -80,6 +135,42 @@ impl Command {
                     Some(shared_env_for_cmds.clone())
                 },
                 content: arg.content.to_owned(),
+                ..CommonContent::default()
+            }),
+            #[cfg(feature = "js")]
+            Command::Js(ref arg) => crate::commands::js::js(&CommonContent {
+                env: {
+                    merge_env(&mut shared_env_for_cmds, &arg.env);
+                    Some(shared_env_for_cmds.clone())
+                },
+                content: arg.content.to_owned(),
+                ..CommonContent::default()
+            }),
+            #[cfg(feature = "lua")]
+            Command::Lua(ref arg) => crate::commands::lua::lua(&CommonContent {
+                env: {
+                    merge_env(&mut shared_env_for_cmds, &arg.env);
+                    Some(shared_env_for_cmds.clone())
+                },
+                content: arg.content.to_owned(),
+                ..CommonContent::default()
+            }),
+            #[cfg(feature = "python")]
+            Command::Python(ref arg) => crate::commands::python::python(&CommonContent {
+                env: {
+                    merge_env(&mut shared_env_for_cmds, &arg.env);
+                    Some(shared_env_for_cmds.clone())
+                },
+                content: arg.content.to_owned(),
+                ..CommonContent::default()
+            }),
+            Command::Script(ref arg) => crate::commands::script::script(&CommonContent {
+                env: {
+                    merge_env(&mut shared_env_for_cmds, &arg.env);
+                    Some(shared_env_for_cmds.clone())
+                },
+                content: arg.content.to_owned(),
+                ..CommonContent::default()
             }),
             Command::SetEnv(ref arg) => crate::commands::set_env::set_env(&CommonContent {
                 env: {
@@ -87,7 +178,159 @@ impl Command {
                     Some(shared_env_for_cmds.clone())
                 },
                 content: arg.content.to_owned(),
+                ..CommonContent::default()
             }),
+            #[cfg(feature = "wasm")]
+            Command::Wasm(ref arg) => crate::commands::wasm::wasm(&WasmCommandArgs {
+                common_content: CommonContent {
+                    env: {
+                        merge_env(&mut shared_env_for_cmds, &arg.common_content.env);
+                        Some(shared_env_for_cmds.clone())
+                    },
+                    content: arg.common_content.content.to_owned(),
+                    ..CommonContent::default()
+                },
+                ..arg.to_owned()
+            }),
+        }
+    }
+
+    /// Whether running this `Command` only reads and writes the env (and prints), so that it
+    /// still runs in a dry run
+    pub fn is_pure(&self) -> bool {
+        match self {
+            Command::Echo(_) | Command::Env(_) | Command::Interpolate(_) | Command::SetEnv(_) => {
+                true
+            }
+            Command::Script(arg) => matches!(
+                arg.content
+                    .as_ref()
+                    .and_then(|c| c.as_str())
+                    .map(parse_shebang),
+                Some(Ok((Shebang::Echo, _)))
+            ),
+            _ => false,
+        }
+    }
+
+    /// Whether this `Command` awaits I/O, so that a timeout interrupts it in place; the others run
+    /// synchronously
+    pub fn is_async(&self) -> bool {
+        matches!(self, Command::Exec(_) | Command::HttpClient(_))
+    }
+
+    /// What this `Command` would do, reported as its content instead of running it:
+    /// - `HttpClient`: the interpolated `method`, `url`, `headers` and `body`;
+    /// - `Jaq`: the interpolated `filter`, once checked to compile;
+    /// - `Exec`: `program`, `args`, `cwd` and `content`, as-is like `commands::exec::exec` runs
+    ///   them;
+    /// - `Wasm`: `path` and `content`, with `fuel`;
+    /// - others: the `Command` without its `env`; a `Script` errs as it would in a real run when
+    ///   its shebang names an unknown interpreter.
+    pub fn dry_run(
+        &self,
+        shared_env_for_cmds: &mut indexmap::IndexMap<String, serde_json::Value>,
+    ) -> Result<CommonContent, VermanSchemaError> {
+        merge_env(shared_env_for_cmds, &self.common_content().env);
+        let report = match self {
+            Command::HttpClient(ref arg) => {
+                let PreparedRequest {
+                    method,
+                    url,
+                    headers,
+                    body,
+                } = prepare_request(&HttpCommandArgs {
+                    common_content: CommonContent {
+                        env: Some(shared_env_for_cmds.clone()),
+                        content: arg.common_content.content.to_owned(),
+                        ..CommonContent::default()
+                    },
+                    ..arg.to_owned()
+                })?;
+                serde_json::json!({
+                    "cmd": "HttpClient",
+                    "method": method.as_str(),
+                    "url": url.to_string(),
+                    "headers": headers
+                        .iter()
+                        .map(|(name, value)| {
+                            (
+                                name.to_string(),
+                                String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                            )
+                        })
+                        .collect::<indexmap::IndexMap<String, String>>(),
+                    "body": body,
+                })
+            }
+            Command::Jaq(ref arg) => {
+                let filter = interpolated_filter(&CommonContent {
+                    env: Some(shared_env_for_cmds.clone()),
+                    content: arg.content.to_owned(),
+                    ..CommonContent::default()
+                })?;
+                vars_filter_from_code(filter.as_str())?;
+                serde_json::json!({"cmd": "Jaq", "filter": filter})
+            }
+            Command::Exec(ref arg) => serde_json::json!({
+                "cmd": "Exec",
+                "program": arg.args.program,
+                "args": arg.args.args,
+                "cwd": arg.args.cwd,
+                "content": arg.common_content.content,
+            }),
+            #[cfg(feature = "wasm")]
+            Command::Wasm(ref arg) => serde_json::json!({
+                "cmd": "Wasm",
+                "path": arg.path,
+                "fuel": arg.fuel,
+                "content": arg.common_content.content,
+            }),
+            _ => {
+                /* errs like `commands::script::script` would */
+                if let Command::Script(ref arg) = self {
+                    match arg.content {
+                        Some(serde_json::Value::String(ref script)) => {
+                            parse_shebang(script)?;
+                        }
+                        _ => return Err(VermanSchemaError::NotFound("String script")),
+                    }
+                }
+                let mut report = serde_json::to_value(self)?;
+                if let Some(object) = report.as_object_mut() {
+                    object.shift_remove("env");
+                }
+                report
+            }
+        };
+        log::info!("Dry run of {}", report);
+        Ok(CommonContent {
+            content: Some(report),
+            env: Some(shared_env_for_cmds.clone()),
+            ..CommonContent::default()
+        })
+    }
+
+    /// `CommonContent` of this `Command`, whose `env` is merged onto the shared env before it
+    /// runs
+    pub fn common_content(&self) -> &CommonContent {
+        match self {
+            Command::Echo(ref arg)
+            | Command::Env(ref arg)
+            | Command::Interpolate(ref arg)
+            | Command::Jaq(ref arg)
+            | Command::Script(ref arg)
+            | Command::SetEnv(ref arg) => arg,
+            Command::Exec(ref arg) => &arg.common_content,
+            Command::HttpClient(ref arg) => &arg.common_content,
+            #[cfg(feature = "js")]
+            Command::Js(ref arg) => arg,
+            #[cfg(feature = "lua")]
+            Command::Lua(ref arg) => arg,
+            #[cfg(feature = "python")]
+            Command::Python(ref arg) => arg,
+            #[cfg(feature = "wasm")]
+            Command::Wasm(ref arg) => &arg.common_content,
         }
     }
 
@@ -109,15 +352,7 @@ impl Command {
                 },
                 content: match common.content {
                     Some(ref content_val) => {
-                        let val: serde_json::Value = match content_val {
-                            serde_json::Value::String(s) => {
-                                match serde_json::from_str(s.as_str()) {
-                                    Ok(v) => v,
-                                    Err(_) => serde_json::Value::String(s.to_owned()),
-                                }
-                            }
-                            x @ _ => x.to_owned(),
-                        };
+                        let val = Command::parsed_content(content_val);
                         shared_env_for_cmds
                             .insert(CommandKey::PreviousContent.to_string(), val.clone());
                         shared_env_for_cmds.insert(
@@ -135,9 +370,21 @@ impl Command {
                     }
                     None => None,
                 },
+                ..CommonContent::default()
             })
         } else {
             Err(VermanSchemaError::NotFound("Nothing to cache"))
         }
     }
+
+    /// `content` as cached by `Command::cache`: JSON strings parsed, anything else as-is
+    pub(crate) fn parsed_content(content: &serde_json::Value) -> serde_json::Value {
+        match content {
+            serde_json::Value::String(s) => match serde_json::from_str(s.as_str()) {
+                Ok(v) => v,
+                Err(_) => serde_json::Value::String(s.to_owned()),
+            },
+            x => x.to_owned(),
+        }
+    }
 }
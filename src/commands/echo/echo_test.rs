@@ -7,6 +7,7 @@ fn echo_simple_test() {
     let b = echo(&CommonContent {
         content: content_val.clone(),
         env: None,
+        ..CommonContent::default()
     })
     .unwrap();
     assert_eq!(b.content, content_val);
@@ -19,6 +20,7 @@ fn echo_env_test() {
         env: Some(indexmap::indexmap! {
             String::from("VAR") => serde_json::Value::String(String::from("${weird}"))
         }),
+        ..CommonContent::default()
     })
     .unwrap();
     assert_eq!(
@@ -7,6 +7,7 @@ fn env_simple_test() {
     let b = env(&CommonContent {
         content: content_val.clone(),
         env: None,
+        ..CommonContent::default()
     })
     .unwrap();
     assert_eq!(b.content, content_val);
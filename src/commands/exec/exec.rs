@@ -0,0 +1,98 @@
+use tokio::io::AsyncWriteExt;
+
+use crate::commands::command::CommandKey;
+use crate::commands::shared::make_subst_map;
+use crate::errors::VermanSchemaError;
+use crate::models::{CommonContent, ExecCommandArgs};
+
+#[derive(derive_more::Display)]
+pub enum ExecKey {
+    #[display("EXEC_STDERR")]
+    Stderr,
+
+    #[display("EXEC_EXIT_CODE")]
+    ExitCode,
+}
+
+/// Run a local program.
+/// - the child's env is only the merged env, stringified as for interpolation; `program`, `args`
+///   and `cwd` are not interpolated, so that shell scripts keep their `$VAR`s
+/// - `CommonContent.content` (else `CMD_PREVIOUS_CONTENT`) is written to stdin, as-is when a
+///   string, else as JSON
+/// - stdout becomes `content` (JSON when it parses as such, else a string); stderr and the exit
+///   code go into the env as `EXEC_STDERR` and `EXEC_EXIT_CODE`
+///
+/// Errs with `UnexpectedExitCode` when the exit code isn't `expectation.exit_code`.
+pub async fn exec(exec_command_args: &ExecCommandArgs) -> Result<CommonContent, VermanSchemaError> {
+    let mut env = exec_command_args
+        .common_content
+        .env
+        .to_owned()
+        .unwrap_or_default();
+    let args = &exec_command_args.args;
+    let mut command = tokio::process::Command::new(&args.program);
+    command.args(&args.args);
+    if let Some(cwd) = &args.cwd {
+        command.current_dir(cwd);
+    }
+    command
+        .env_clear()
+        .envs(make_subst_map(&env))
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        /* e.g., when `Task.timeout_ms` runs out */
+        .kill_on_drop(true);
+
+    let stdin_bytes = match exec_command_args.common_content.content.to_owned().or_else(|| {
+        env.get(CommandKey::PreviousContent.to_string().as_str())
+            .cloned()
+    }) {
+        Some(serde_json::Value::String(s)) => s.into_bytes(),
+        Some(serde_json::Value::Null) | None => Vec::new(),
+        Some(val) => serde_json::to_vec(&val)?,
+    };
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| VermanSchemaError::TaskFailedToStart(format!("{}: {}", args.program, e)))?;
+    let mut stdin = child.stdin.take().unwrap();
+    let (written, output) = tokio::join!(
+        async move { stdin.write_all(&stdin_bytes).await },
+        child.wait_with_output()
+    );
+    let output = output?;
+    /* A child that exits without reading all of stdin is fine */
+    if let Err(e) = written {
+        if e.kind() != std::io::ErrorKind::BrokenPipe {
+            return Err(e.into());
+        }
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    /* `None` when killed by a signal */
+    let exit_code = output.status.code().unwrap_or(-1);
+    if exit_code != exec_command_args.expectation.exit_code {
+        log::error!("{} exited with {}:\n{}", args.program, exit_code, stderr);
+        return Err(VermanSchemaError::UnexpectedExitCode {
+            program: args.program.to_owned(),
+            actual: exit_code,
+            expected: exec_command_args.expectation.exit_code,
+        });
+    }
+
+    env.insert(ExecKey::Stderr.to_string(), serde_json::Value::String(stderr));
+    env.insert(ExecKey::ExitCode.to_string(), exit_code.into());
+    Ok(CommonContent {
+        content: Some(
+            serde_json::from_str(&stdout).unwrap_or(serde_json::Value::String(stdout)),
+        ),
+        env: Some(env),
+        ..CommonContent::default()
+    })
+}
+
+#[cfg(test)]
+#[path = "exec_test.rs"]
+mod tests;
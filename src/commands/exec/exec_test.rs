@@ -0,0 +1,107 @@
+use super::*;
+use crate::models::{CommonContent, ExecArgs, ExecCommandArgs, Expectation};
+
+fn sh(script: &str) -> ExecArgs {
+    ExecArgs {
+        program: String::from("/bin/sh"),
+        args: vec![String::from("-c"), String::from(script)],
+        cwd: None,
+    }
+}
+
+#[tokio::test]
+async fn exec_stdin_env_and_output_test() {
+    let common_output = exec(&ExecCommandArgs {
+        args: sh("printf '{\"in\": %s, \"who\": \"%s\"}' \"$(cat)\" \"$WHO\"; echo oops >&2"),
+        common_content: CommonContent {
+            content: None,
+            env: Some(indexmap::indexmap! {
+                String::from("WHO") => serde_json::Value::String(String::from("sh")),
+                CommandKey::PreviousContent.to_string() => serde_json::json!([1, 2]),
+            }),
+            ..CommonContent::default()
+        },
+        ..ExecCommandArgs::default()
+    })
+    .await
+    .unwrap();
+    assert_eq!(
+        common_output.content,
+        Some(serde_json::json!({"in": [1, 2], "who": "sh"}))
+    );
+    let env = common_output.env.unwrap();
+    assert_eq!(
+        env.get(ExecKey::Stderr.to_string().as_str()),
+        Some(&serde_json::Value::String(String::from("oops\n")))
+    );
+    assert_eq!(
+        env.get(ExecKey::ExitCode.to_string().as_str()),
+        Some(&serde_json::json!(0))
+    );
+}
+
+#[tokio::test]
+async fn exec_exit_code_test() {
+    let args = ExecCommandArgs {
+        args: sh("exit 3"),
+        ..ExecCommandArgs::default()
+    };
+    match exec(&args).await {
+        Err(error @ VermanSchemaError::UnexpectedExitCode { .. }) => {
+            assert!(matches!(
+                error,
+                VermanSchemaError::UnexpectedExitCode {
+                    actual: 3,
+                    expected: 0,
+                    ..
+                }
+            ));
+            assert_eq!(
+                std::process::Termination::report(error),
+                std::process::ExitCode::from(3)
+            );
+        }
+        other => panic!("expected `UnexpectedExitCode` got {:?}", other),
+    }
+    /* exiting with `0` when another code was expected still fails the process */
+    assert_eq!(
+        std::process::Termination::report(VermanSchemaError::UnexpectedExitCode {
+            program: String::from("sh"),
+            actual: 0,
+            expected: 3,
+        }),
+        std::process::ExitCode::FAILURE
+    );
+    assert_eq!(
+        std::process::Termination::report(VermanSchemaError::UnexpectedExitCode {
+            program: String::from("sh"),
+            actual: 256,
+            expected: 0,
+        }),
+        std::process::ExitCode::FAILURE
+    );
+    assert!(exec(&ExecCommandArgs {
+        expectation: Expectation {
+            exit_code: 3,
+            ..Expectation::default()
+        },
+        ..args
+    })
+    .await
+    .is_ok());
+}
+
+#[tokio::test]
+async fn exec_failed_to_start_test() {
+    assert!(matches!(
+        exec(&ExecCommandArgs {
+            args: ExecArgs {
+                program: String::from("/nonexistent/verman"),
+                ..ExecArgs::default()
+            },
+            ..ExecCommandArgs::default()
+        })
+        .await,
+        Err(VermanSchemaError::TaskFailedToStart(_))
+    ));
+}
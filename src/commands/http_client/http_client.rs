@@ -16,52 +16,14 @@ pub async fn http(
     /*******************
      * Prepare request *
      *******************/
-    let mut body = http_command_args.common_content.content.to_owned();
-    let env = http_command_args
-        .common_content
-        .env
-        .to_owned()
-        .unwrap_or_else(|| indexmap::IndexMap::<String, serde_json::Value>::new());
-    body = body.or_else(|| {
-        env.get(CommandKey::PreviousContent.to_string().as_str())
-            .cloned()
-    });
-
-    let mut args = http_command_args.args.to_owned();
-    let variables: std::collections::HashMap<String, String> = if env.is_empty() {
-        std::collections::HashMap::<String, String>::new()
-    } else {
-        /* Do interpolation and ensure input is set */
-        let variables = make_subst_map(&env);
-
-        args.method = http::method::Method::from_str(
-            subst::substitute(args.method.to_string().as_str(), &variables)?.as_str(),
-        )?;
-        args.url = http::uri::Uri::from_str(
-            subst::substitute(args.url.to_string().as_str(), &variables)?.as_str(),
-        )?;
-
-        match body {
-            Some(serde_json::Value::String(s)) => {
-                body = Some(serde_json::Value::String(subst::substitute(
-                    s.as_str(),
-                    &variables,
-                )?));
-            }
-            Some(val) => {
-                body = Some(serde_json::from_str(
-                    subst::substitute(serde_json::to_string(&val)?.as_str(), &variables)?.as_str(),
-                )?);
-            }
-            _ => {}
-        };
-        variables
-    };
+    let PreparedRequest {
+        method,
+        url,
+        headers,
+        body,
+    } = prepare_request(http_command_args)?;
     let client = reqwest::Client::new();
-    let mut req = client.request(args.method, args.url.to_string());
-    if let Some(headers) = args.headers {
-        req = req.headers(indexmap_of_ValueNoObj_to_HeaderMap(&headers, &variables)?);
-    }
+    let mut req = client.request(method, url.to_string()).headers(headers);
     if let Some(val) = body {
         req = req.json(&val);
     }
@@ -130,6 +92,70 @@ pub async fn http(
     }
 }
 
+/// Request `http` sends, once interpolated
+#[derive(Clone, Debug)]
+pub(crate) struct PreparedRequest {
+    pub(crate) method: http::method::Method,
+    pub(crate) url: http::uri::Uri,
+    pub(crate) headers: http::header::HeaderMap,
+    pub(crate) body: Option<serde_json::Value>,
+}
+
+pub(crate) fn prepare_request(
+    http_command_args: &HttpCommandArgs,
+) -> Result<PreparedRequest, VermanSchemaError> {
+    let mut body = http_command_args.common_content.content.to_owned();
+    let env = http_command_args
+        .common_content
+        .env
+        .to_owned()
+        .unwrap_or_else(|| indexmap::IndexMap::<String, serde_json::Value>::new());
+    body = body.or_else(|| {
+        env.get(CommandKey::PreviousContent.to_string().as_str())
+            .cloned()
+    });
+
+    let mut args = http_command_args.args.to_owned();
+    let variables: std::collections::HashMap<String, String> = if env.is_empty() {
+        std::collections::HashMap::<String, String>::new()
+    } else {
+        /* Do interpolation and ensure input is set */
+        let variables = make_subst_map(&env);
+
+        args.method = http::method::Method::from_str(
+            subst::substitute(args.method.to_string().as_str(), &variables)?.as_str(),
+        )?;
+        args.url = http::uri::Uri::from_str(
+            subst::substitute(args.url.to_string().as_str(), &variables)?.as_str(),
+        )?;
+
+        match body {
+            Some(serde_json::Value::String(s)) => {
+                body = Some(serde_json::Value::String(subst::substitute(
+                    s.as_str(),
+                    &variables,
+                )?));
+            }
+            Some(val) => {
+                body = Some(serde_json::from_str(
+                    subst::substitute(serde_json::to_string(&val)?.as_str(), &variables)?.as_str(),
+                )?);
+            }
+            _ => {}
+        };
+        variables
+    };
+    Ok(PreparedRequest {
+        headers: match args.headers {
+            Some(headers) => indexmap_of_ValueNoObj_to_HeaderMap(&headers, &variables)?,
+            None => http::header::HeaderMap::new(),
+        },
+        method: args.method,
+        url: args.url,
+        body,
+    })
+}
+
 #[allow(non_snake_case)]
 fn indexmap_of_ValueNoObj_to_HeaderMap(
     v: &Vec<indexmap::IndexMap<String, serde_json_extensions::ValueNoObjOrArr>>,
@@ -21,6 +21,7 @@ pub fn interpolate(common_content: &CommonContent) -> Result<CommonContent, Verm
                     serde_json::from_str(content_s.as_str())?
                 }),
                 env: Some(env.to_owned()),
+                ..CommonContent::default()
             })
         } else {
             Ok(common_content.to_owned())
@@ -11,6 +11,7 @@ fn interpolate_test() {
             String::from("C") => serde_json::Value::String(String::from("$B")),
             String::from("D") => serde_json::Value::String(String::from("goal")),
         }),
+        ..CommonContent::default()
     })
     .unwrap();
     assert_eq!(
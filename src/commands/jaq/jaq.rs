@@ -5,13 +5,10 @@ use crate::commands::shared::interpolate_input_else_get_prior_output;
 use crate::errors::VermanSchemaError;
 use crate::models::CommonContent;
 
-mod jaq_utils;
+pub(crate) mod jaq_utils;
 
 pub fn jaq(common_content: &CommonContent) -> Result<CommonContent, VermanSchemaError> {
-    let common_content_out = interpolate_input_else_get_prior_output(common_content, true)?;
-
-    let content = common_content_out.content.clone();
-    let env = common_content_out.env.clone();
+    let env = common_content.env.clone();
 
     let input = Box::new(std::iter::once(Ok(match env {
         None => Err(VermanSchemaError::NotFound("Any content")),
@@ -23,11 +20,7 @@ pub fn jaq(common_content: &CommonContent) -> Result<CommonContent, VermanSchema
             Ok(jaq_json::Val::from(json))
         }
     }?)));
-    let content_vec = content.ok_or_else(|| VermanSchemaError::NotFound("Any filter"))?;
-    let filter = match content_vec {
-        serde_json::Value::String(s) => Ok(s),
-        _ => Err(VermanSchemaError::NotFound("String filter")),
-    }?;
+    let filter = interpolated_filter(common_content)?;
 
     let (vars, filter) = jaq_utils::vars_filter_from_code(filter.as_str())?;
 
@@ -46,6 +39,18 @@ pub fn jaq(common_content: &CommonContent) -> Result<CommonContent, VermanSchema
     })
 }
 
+/// Filter of `common_content` once interpolated, as `jaq` runs it
+pub(crate) fn interpolated_filter(
+    common_content: &CommonContent,
+) -> Result<String, VermanSchemaError> {
+    let common_content_out = interpolate_input_else_get_prior_output(common_content, true)?;
+    match common_content_out.content {
+        Some(serde_json::Value::String(s)) => Ok(s),
+        Some(_) => Err(VermanSchemaError::NotFound("String filter")),
+        None => Err(VermanSchemaError::NotFound("Any filter")),
+    }
+}
+
 fn jaq_runner(
     filter: &jaq_core::Filter<jaq_core::Native<jaq_json::Val>>,
     vars: Vec<jaq_json::Val>,
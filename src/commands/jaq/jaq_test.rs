@@ -66,6 +66,7 @@ fn test_jaq() {
         env: Some(indexmap::indexmap! {
             CommandKey::PreviousContent.to_string() => serde_json::json!([1,{"stuff": true}])
         }),
+        ..CommonContent::default()
     };
     let result_common = jaq(&input_common).unwrap();
     assert_eq!(
@@ -0,0 +1,85 @@
+use rquickjs::CatchResultExt;
+
+use crate::commands::command::CommandKey;
+use crate::errors::VermanSchemaError;
+use crate::models::CommonContent;
+
+/// Heap limit of the QuickJS runtime, in bytes
+pub const MEMORY_LIMIT: usize = 256 * 1024 * 1024;
+
+/// Wall-clock limit after which the running script is interrupted
+pub const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Run JavaScript from `CommonContent.content` in an embedded QuickJS with the globals:
+/// - `env`, an object of the merged env; changes to it are merged back into the shared env
+/// - `input`, the `CMD_PREVIOUS_CONTENT`
+///
+/// The code is the body of a function, so whatever it `return`s (as JSON) becomes `content`.
+/// The engine has no network, filesystem nor module loading; TypeScript must be compiled to
+/// JavaScript beforehand.
+/// Scripts are limited to `MEMORY_LIMIT` and interrupted after `TIMEOUT`.
+pub fn js(common_content: &CommonContent) -> Result<CommonContent, VermanSchemaError> {
+    js_with_timeout(common_content, TIMEOUT)
+}
+
+fn js_with_timeout(
+    common_content: &CommonContent,
+    timeout: std::time::Duration,
+) -> Result<CommonContent, VermanSchemaError> {
+    let code = match common_content.content {
+        Some(serde_json::Value::String(ref s)) => Ok(s),
+        _ => Err(VermanSchemaError::NotFound("String JavaScript code")),
+    }?;
+    let env = common_content.env.to_owned().unwrap_or_default();
+    let input = env
+        .get(CommandKey::PreviousContent.to_string().as_str())
+        .unwrap_or(&serde_json::Value::Null);
+
+    let runtime = rquickjs::Runtime::new().map_err(js_error)?;
+    runtime.set_memory_limit(MEMORY_LIMIT);
+    let deadline = std::time::Instant::now() + timeout;
+    runtime.set_interrupt_handler(Some(Box::new(move || {
+        std::time::Instant::now() >= deadline
+    })));
+    let context = rquickjs::Context::full(&runtime).map_err(js_error)?;
+    let result = context.with(|ctx| -> Result<String, VermanSchemaError> {
+        let globals = ctx.globals();
+        globals
+            .set(
+                "env",
+                ctx.json_parse(serde_json::to_string(&env)?)
+                    .map_err(js_error)?,
+            )
+            .map_err(js_error)?;
+        globals
+            .set(
+                "input",
+                ctx.json_parse(serde_json::to_string(input)?)
+                    .map_err(js_error)?,
+            )
+            .map_err(js_error)?;
+        ctx.eval::<String, _>(format!(
+            "JSON.stringify([(function () {{\n{}\n}})(), env]);",
+            code
+        ))
+        .catch(&ctx)
+        .map_err(|error| VermanSchemaError::JsError(error.to_string()))
+    })?;
+    let (content, env_out): (
+        serde_json::Value,
+        indexmap::IndexMap<String, serde_json::Value>,
+    ) = serde_json::from_str(&result)?;
+    Ok(CommonContent {
+        content: Some(content),
+        env: Some(env_out),
+        ..CommonContent::default()
+    })
+}
+
+fn js_error(error: rquickjs::Error) -> VermanSchemaError {
+    VermanSchemaError::JsError(error.to_string())
+}
+
+#[cfg(test)]
+#[path = "js_test.rs"]
+mod tests;
@@ -0,0 +1,70 @@
+use super::*;
+use crate::models::CommonContent;
+
+#[test]
+fn js_input_and_env_test() {
+    let common_output = js(&CommonContent {
+        content: Some(serde_json::Value::String(String::from(
+            "env.GREETING = `hello ${env.WHO}`;\nreturn { total: input[0] + input[1], who: env.WHO };",
+        ))),
+        env: Some(indexmap::indexmap! {
+            String::from("WHO") => serde_json::Value::String(String::from("JS")),
+            CommandKey::PreviousContent.to_string() => serde_json::json!([2, 3]),
+        }),
+        ..CommonContent::default()
+    })
+    .unwrap();
+    assert_eq!(
+        common_output.content,
+        Some(serde_json::json!({"total": 5, "who": "JS"}))
+    );
+    assert_eq!(
+        common_output.env.unwrap().get("GREETING"),
+        Some(&serde_json::Value::String(String::from("hello JS")))
+    );
+}
+
+#[test]
+fn js_undefined_and_error_test() {
+    let common_output = js(&CommonContent {
+        content: Some(serde_json::Value::String(String::from("let x = 1;"))),
+        env: None,
+        ..CommonContent::default()
+    })
+    .unwrap();
+    assert_eq!(common_output.content, Some(serde_json::Value::Null));
+
+    assert!(matches!(
+        js(&CommonContent {
+            content: Some(serde_json::Value::String(String::from(
+                "return require('fs');"
+            ))),
+            env: None,
+            ..CommonContent::default()
+        }),
+        Err(VermanSchemaError::JsError(_))
+    ));
+}
+
+#[test]
+fn js_limits_test() {
+    let endless = CommonContent {
+        content: Some(serde_json::Value::String(String::from("while (true) {}"))),
+        env: None,
+        ..CommonContent::default()
+    };
+    assert!(matches!(
+        js_with_timeout(&endless, std::time::Duration::from_millis(50)),
+        Err(VermanSchemaError::JsError(_))
+    ));
+    assert!(matches!(
+        js(&CommonContent {
+            content: Some(serde_json::Value::String(String::from(
+                "const a = []; while (true) { a.push('x'.repeat(1024)); }"
+            ))),
+            env: None,
+            ..CommonContent::default()
+        }),
+        Err(VermanSchemaError::JsError(_))
+    ));
+}
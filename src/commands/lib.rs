@@ -6,6 +6,9 @@ pub mod echo;
 #[path = "env/env.rs"]
 pub mod env;
 
+#[path = "exec/exec.rs"]
+pub mod exec;
+
 #[path = "http_client/http_client.rs"]
 pub mod http_client;
 
@@ -15,9 +18,28 @@ pub mod interpolate;
 #[path = "jaq/jaq.rs"]
 pub mod jaq;
 
+#[cfg(feature = "js")]
+#[path = "js/js.rs"]
+pub mod js;
+
+#[cfg(feature = "lua")]
+#[path = "lua/lua.rs"]
+pub mod lua;
+
+#[cfg(feature = "python")]
+#[path = "python/python.rs"]
+pub mod python;
+
+#[path = "script/script.rs"]
+pub mod script;
+
 #[path = "set_env/set_env.rs"]
 pub mod set_env;
 
+#[cfg(feature = "wasm")]
+#[path = "wasm/wasm.rs"]
+pub mod wasm;
+
 /*pub const VALID_COMMANDS_SET: std::collections::HashSet<&'static str> =
 std::collections::HashSet::<&'static str>::from(VALID_COMMANDS);*/
 pub mod command;
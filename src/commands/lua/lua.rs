@@ -0,0 +1,129 @@
+use mlua::LuaSerdeExt;
+
+use crate::commands::command::CommandKey;
+use crate::errors::VermanSchemaError;
+use crate::models::CommonContent;
+
+/// Heap limit of the Lua state, in bytes
+pub const MEMORY_LIMIT: usize = 256 * 1024 * 1024;
+
+/// Wall-clock limit after which the running chunk is interrupted
+pub const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Instructions between two checks of the `TIMEOUT` deadline
+const INSTRUCTIONS_PER_CHECK: u32 = 10_000;
+
+/// Run Lua code from `CommonContent.content` with the globals:
+/// - `env`, a table of the merged env; changes to it are merged back into the shared env
+/// - `input`, the `CMD_PREVIOUS_CONTENT`
+///
+/// The value the chunk `return`s becomes `content`.
+/// Only the `coroutine`, `table`, `string`, `utf8` and `math` standard libraries are loaded, and
+/// `dofile`, `loadfile`, `load` and `collectgarbage` are removed, so there is no `io`, `os` nor
+/// `require`.
+///
+/// Chunks are limited to `MEMORY_LIMIT` and interrupted after `TIMEOUT`.
+///
+/// Lua tables have no key order: keys of `env` (and of the objects in it) keep their original
+/// order, new keys follow sorted by name.
+pub fn lua(common_content: &CommonContent) -> Result<CommonContent, VermanSchemaError> {
+    lua_with_timeout(common_content, TIMEOUT)
+}
+
+fn lua_with_timeout(
+    common_content: &CommonContent,
+    timeout: std::time::Duration,
+) -> Result<CommonContent, VermanSchemaError> {
+    let code = match common_content.content {
+        Some(serde_json::Value::String(ref s)) => Ok(s),
+        _ => Err(VermanSchemaError::NotFound("String Lua code")),
+    }?;
+    let env = common_content.env.to_owned().unwrap_or_default();
+
+    let lua = mlua::Lua::new_with(
+        mlua::StdLib::COROUTINE
+            | mlua::StdLib::TABLE
+            | mlua::StdLib::STRING
+            | mlua::StdLib::UTF8
+            | mlua::StdLib::MATH,
+        mlua::LuaOptions::default(),
+    )?;
+    lua.set_memory_limit(MEMORY_LIMIT)?;
+    let deadline = std::time::Instant::now() + timeout;
+    lua.set_hook(
+        mlua::HookTriggers::new().every_nth_instruction(INSTRUCTIONS_PER_CHECK),
+        move |_, _| {
+            if std::time::Instant::now() >= deadline {
+                Err(mlua::Error::runtime(format!(
+                    "interrupted after {}ms",
+                    timeout.as_millis()
+                )))
+            } else {
+                Ok(())
+            }
+        },
+    );
+    let globals = lua.globals();
+    for name in ["dofile", "loadfile", "load", "collectgarbage"] {
+        globals.set(name, mlua::Value::Nil)?;
+    }
+    globals.set("env", lua.to_value(&env)?)?;
+    globals.set(
+        "input",
+        lua.to_value(
+            env.get(CommandKey::PreviousContent.to_string().as_str())
+                .unwrap_or(&serde_json::Value::Null),
+        )?,
+    )?;
+
+    let result: mlua::Value = lua.load(code.as_str()).set_name("<inline>").eval()?;
+    let options = mlua::DeserializeOptions::new().sort_keys(true);
+    let content: serde_json::Value = lua.from_value_with(result, options)?;
+    let mut env_out: indexmap::IndexMap<String, serde_json::Value> =
+        lua.from_value_with(globals.get::<_, mlua::Value>("env")?, options)?;
+    let mut ordered_env = indexmap::IndexMap::with_capacity(env_out.len());
+    for (key, original) in env.iter() {
+        if let Some(value) = env_out.shift_remove(key) {
+            ordered_env.insert(key.to_owned(), ordered_like(original, value));
+        }
+    }
+    ordered_env.extend(env_out);
+    Ok(CommonContent {
+        content: Some(content),
+        env: Some(ordered_env),
+        ..CommonContent::default()
+    })
+}
+
+/// `value` with the object keys it shares with `original` back in their original order
+fn ordered_like(original: &serde_json::Value, value: serde_json::Value) -> serde_json::Value {
+    match (original, value) {
+        (serde_json::Value::Object(original), serde_json::Value::Object(mut map)) => {
+            let mut ordered = serde_json::Map::with_capacity(map.len());
+            for (key, original) in original.iter() {
+                if let Some(value) = map.shift_remove(key) {
+                    ordered.insert(key.to_owned(), ordered_like(original, value));
+                }
+            }
+            ordered.extend(map);
+            serde_json::Value::Object(ordered)
+        }
+        (serde_json::Value::Array(original), serde_json::Value::Array(values)) => {
+            serde_json::Value::Array(
+                values
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, value)| match original.get(i) {
+                        Some(original) => ordered_like(original, value),
+                        None => value,
+                    })
+                    .collect(),
+            )
+        }
+        (_, value) => value,
+    }
+}
+
+#[cfg(test)]
+#[path = "lua_test.rs"]
+mod tests;
@@ -0,0 +1,94 @@
+use super::*;
+use crate::models::CommonContent;
+
+#[test]
+fn lua_input_and_env_test() {
+    let common_output = lua(&CommonContent {
+        content: Some(serde_json::Value::String(String::from(
+            "env.GREETING = 'hello ' .. env.WHO\nreturn { total = input[1] + input[2], who = env.WHO }",
+        ))),
+        env: Some(indexmap::indexmap! {
+            String::from("WHO") => serde_json::Value::String(String::from("Lua")),
+            CommandKey::PreviousContent.to_string() => serde_json::json!([2, 3]),
+        }),
+        ..CommonContent::default()
+    })
+    .unwrap();
+    assert_eq!(
+        common_output.content,
+        Some(serde_json::json!({"total": 5, "who": "Lua"}))
+    );
+    assert_eq!(
+        common_output.env.unwrap().get("GREETING"),
+        Some(&serde_json::Value::String(String::from("hello Lua")))
+    );
+}
+
+#[test]
+fn lua_sandbox_test() {
+    assert!(lua(&CommonContent {
+        content: Some(serde_json::Value::String(String::from(
+            "return os.getenv('HOME')"
+        ))),
+        env: None,
+        ..CommonContent::default()
+    })
+    .is_err());
+    for code in [
+        "return dofile('/etc/passwd')",
+        "return loadfile('/etc/passwd')",
+        "return load('return 1')()",
+        "return collectgarbage('count')",
+    ] {
+        assert!(lua(&CommonContent {
+            content: Some(serde_json::Value::String(String::from(code))),
+            env: None,
+            ..CommonContent::default()
+        })
+        .is_err());
+    }
+}
+
+#[test]
+fn lua_env_order_test() {
+    let env = lua(&CommonContent {
+        content: Some(serde_json::Value::String(String::from(
+            "env.NEW_B = 1\nenv.NEW_A = {}\nreturn nil",
+        ))),
+        env: Some(indexmap::indexmap! {
+            String::from("Z") => serde_json::json!({"z": 1, "a": {}, "m": []}),
+            String::from("A") => serde_json::json!({}),
+        }),
+        ..CommonContent::default()
+    })
+    .unwrap()
+    .env
+    .unwrap();
+    assert_eq!(
+        serde_json::to_string(&env).unwrap(),
+        r#"{"Z":{"z":1,"a":{},"m":[]},"A":{},"NEW_A":{},"NEW_B":1}"#
+    );
+}
+
+#[test]
+fn lua_limits_test() {
+    let endless = CommonContent {
+        content: Some(serde_json::Value::String(String::from("while true do end"))),
+        env: None,
+        ..CommonContent::default()
+    };
+    assert!(matches!(
+        lua_with_timeout(&endless, std::time::Duration::from_millis(50)),
+        Err(VermanSchemaError::LuaError { .. })
+    ));
+    assert!(matches!(
+        lua(&CommonContent {
+            content: Some(serde_json::Value::String(String::from(
+                "return string.rep('x', 2^29)"
+            ))),
+            env: None,
+            ..CommonContent::default()
+        }),
+        Err(VermanSchemaError::LuaError { .. })
+    ));
+}
@@ -0,0 +1,57 @@
+use pyo3::types::{PyAnyMethods, PyDict, PyDictMethods, PyTracebackMethods};
+
+use crate::commands::command::CommandKey;
+use crate::errors::VermanSchemaError;
+use crate::models::CommonContent;
+
+/// Run the Python script in `CommonContent.content` with the globals:
+/// - `env`, a dict of the merged env; changes to it are merged back into the shared env
+/// - `input`, the `CMD_PREVIOUS_CONTENT`
+///
+/// Whatever the script assigns to `result` becomes `content` (`None` when unassigned).
+/// Values cross the boundary as JSON, so they must be `json.dumps`-able.
+/// Unlike `Command::Js` and `Command::Lua` this is not sandboxed: the interpreter is CPython.
+pub fn python(common_content: &CommonContent) -> Result<CommonContent, VermanSchemaError> {
+    let code = match common_content.content {
+        Some(serde_json::Value::String(ref s)) => Ok(s),
+        _ => Err(VermanSchemaError::NotFound("String Python code")),
+    }?;
+    let env = common_content.env.to_owned().unwrap_or_default();
+    let input = env
+        .get(CommandKey::PreviousContent.to_string().as_str())
+        .unwrap_or(&serde_json::Value::Null);
+    let (env_s, input_s) = (serde_json::to_string(&env)?, serde_json::to_string(input)?);
+
+    pyo3::prepare_freethreaded_python();
+    let (content_s, env_out_s) = pyo3::Python::with_gil(|py| {
+        let run = || -> pyo3::PyResult<(String, String)> {
+            let json = py.import_bound("json")?;
+            let globals = PyDict::new_bound(py);
+            globals.set_item("env", json.call_method1("loads", (env_s,))?)?;
+            globals.set_item("input", json.call_method1("loads", (input_s,))?)?;
+            py.run_bound(code, Some(&globals), None)?;
+            let result = globals.get_item("result")?;
+            Ok((
+                json.call_method1("dumps", (result,))?.extract()?,
+                json.call_method1("dumps", (globals.get_item("env")?,))?
+                    .extract()?,
+            ))
+        };
+        run().map_err(|error| VermanSchemaError::PythonError {
+            message: error.to_string(),
+            traceback: error
+                .traceback_bound(py)
+                .and_then(|traceback| traceback.format().ok())
+                .unwrap_or_default(),
+        })
+    })?;
+    Ok(CommonContent {
+        content: Some(serde_json::from_str(&content_s)?),
+        env: Some(serde_json::from_str(&env_out_s)?),
+        ..CommonContent::default()
+    })
+}
+
+#[cfg(test)]
+#[path = "python_test.rs"]
+mod tests;
@@ -0,0 +1,42 @@
+use super::*;
+use crate::models::CommonContent;
+
+#[test]
+fn python_input_and_env_test() {
+    let common_output = python(&CommonContent {
+        content: Some(serde_json::Value::String(String::from(
+            "env['GREETING'] = 'hello ' + env['WHO']\nresult = {'total': sum(input), 'who': env['WHO']}",
+        ))),
+        env: Some(indexmap::indexmap! {
+            String::from("WHO") => serde_json::Value::String(String::from("Python")),
+            CommandKey::PreviousContent.to_string() => serde_json::json!([2, 3]),
+        }),
+        ..CommonContent::default()
+    })
+    .unwrap();
+    assert_eq!(
+        common_output.content,
+        Some(serde_json::json!({"total": 5, "who": "Python"}))
+    );
+    assert_eq!(
+        common_output.env.unwrap().get("GREETING"),
+        Some(&serde_json::Value::String(String::from("hello Python")))
+    );
+}
+
+#[test]
+fn python_exception_test() {
+    match python(&CommonContent {
+        content: Some(serde_json::Value::String(String::from(
+            "def fail():\n    raise ValueError('nope')\nfail()",
+        ))),
+        env: None,
+        ..CommonContent::default()
+    }) {
+        Err(VermanSchemaError::PythonError { message, traceback }) => {
+            assert_eq!(message, "ValueError: nope");
+            assert!(traceback.contains("in fail"), "{}", traceback);
+        }
+        other => panic!("expected `PythonError`, got {:?}", other),
+    }
+}
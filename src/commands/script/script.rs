@@ -0,0 +1,114 @@
+use crate::errors::VermanSchemaError;
+use crate::models::CommonContent;
+use crate::verman_schema::VermanConfig;
+
+/// Interpreter chosen by the first line of a script, see `VermanConfig.shell`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, derive_more::Display)]
+pub enum Shebang {
+    #[display("#!/echo")]
+    Echo,
+
+    #[display("#!/jq")]
+    Jq,
+
+    #[display("#!/deno")]
+    Deno,
+
+    #[display("#!/wasm")]
+    Wasm,
+
+    #[display("#!/js")]
+    Js,
+
+    #[display("#!/lua")]
+    Lua,
+
+    #[display("#!/python")]
+    Python,
+}
+
+impl std::str::FromStr for Shebang {
+    type Err = VermanSchemaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim_end() {
+            "#!/echo" => Ok(Self::Echo),
+            "#!/jq" => Ok(Self::Jq),
+            "#!/deno" => Ok(Self::Deno),
+            "#!/wasm" => Ok(Self::Wasm),
+            "#!/js" => Ok(Self::Js),
+            "#!/lua" => Ok(Self::Lua),
+            "#!/python" => Ok(Self::Python),
+            interpreter => Err(VermanSchemaError::NotInstalled(format!(
+                "{:?} interpreter",
+                interpreter
+            ))),
+        }
+    }
+}
+
+/// Split `script` into its `Shebang` and body.
+/// Without a shebang line the default `VermanConfig.shell` is used and the whole script is the body.
+pub fn parse_shebang(script: &str) -> Result<(Shebang, &str), VermanSchemaError> {
+    if script.starts_with("#!") {
+        let (first_line, body) = script.split_once('\n').unwrap_or((script, ""));
+        Ok((first_line.parse()?, body))
+    } else {
+        Ok((VermanConfig::default().shell().parse()?, script))
+    }
+}
+
+/// Run `CommonContent.content` with the interpreter its first line names.
+/// The body (everything after the shebang line) becomes the `content` of that interpreter.
+pub fn script(common_content: &CommonContent) -> Result<CommonContent, VermanSchemaError> {
+    let script = match common_content.content {
+        Some(serde_json::Value::String(ref s)) => Ok(s),
+        _ => Err(VermanSchemaError::NotFound("String script")),
+    }?;
+    let (shebang, body) = parse_shebang(script)?;
+    let body_common_content = CommonContent {
+        content: Some(serde_json::Value::String(body.to_string())),
+        env: common_content.env.to_owned(),
+        ..CommonContent::default()
+    };
+    match shebang {
+        Shebang::Echo => crate::commands::echo::echo(&body_common_content),
+        Shebang::Jq => crate::commands::jaq::jaq(&body_common_content),
+        #[cfg(feature = "js")]
+        Shebang::Deno | Shebang::Js => crate::commands::js::js(&body_common_content),
+        #[cfg(not(feature = "js"))]
+        Shebang::Deno | Shebang::Js => Err(VermanSchemaError::NotInstalled(format!(
+            "{} requires feature `js`",
+            shebang
+        ))),
+        #[cfg(feature = "wasm")]
+        Shebang::Wasm => crate::commands::wasm::wasm(&crate::models::WasmCommandArgs {
+            path: body.trim().to_string(),
+            common_content: body_common_content,
+            ..Default::default()
+        }),
+        #[cfg(not(feature = "wasm"))]
+        Shebang::Wasm => Err(VermanSchemaError::NotInstalled(format!(
+            "{} requires feature `wasm`",
+            shebang
+        ))),
+        #[cfg(feature = "lua")]
+        Shebang::Lua => crate::commands::lua::lua(&body_common_content),
+        #[cfg(not(feature = "lua"))]
+        Shebang::Lua => Err(VermanSchemaError::NotInstalled(format!(
+            "{} requires feature `lua`",
+            shebang
+        ))),
+        #[cfg(feature = "python")]
+        Shebang::Python => crate::commands::python::python(&body_common_content),
+        #[cfg(not(feature = "python"))]
+        Shebang::Python => Err(VermanSchemaError::NotInstalled(format!(
+            "{} requires feature `python`",
+            shebang
+        ))),
+    }
+}
+
+#[cfg(test)]
+#[path = "script_test.rs"]
+mod tests;
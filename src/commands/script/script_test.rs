@@ -0,0 +1,65 @@
+use super::*;
+use crate::commands::command::CommandKey;
+use crate::models::CommonContent;
+
+#[test]
+fn parse_shebang_test() {
+    assert_eq!(
+        parse_shebang("#!/echo\nhello\nworld").unwrap(),
+        (Shebang::Echo, "hello\nworld")
+    );
+    assert_eq!(parse_shebang(".[0]").unwrap(), (Shebang::Jq, ".[0]"));
+    assert_eq!(parse_shebang("#!/lua").unwrap(), (Shebang::Lua, ""));
+}
+
+#[test]
+fn script_echo_test() {
+    let common_output = script(&CommonContent {
+        content: Some(serde_json::Value::String(String::from(
+            "#!/echo\nHello ${VAR}",
+        ))),
+        env: Some(indexmap::indexmap! {
+            String::from("VAR") => serde_json::Value::String(String::from("world"))
+        }),
+        ..CommonContent::default()
+    })
+    .unwrap();
+    assert_eq!(
+        common_output.content,
+        Some(serde_json::Value::String(String::from("Hello world")))
+    );
+}
+
+#[test]
+fn script_jq_test() {
+    let common_output = script(&CommonContent {
+        content: Some(serde_json::Value::String(String::from("#!/jq\n.[1]"))),
+        env: Some(indexmap::indexmap! {
+            CommandKey::PreviousContent.to_string() => serde_json::json!([1, {"stuff": true}])
+        }),
+        ..CommonContent::default()
+    })
+    .unwrap();
+    assert_eq!(
+        common_output.content,
+        Some(serde_json::Value::String(String::from("{\"stuff\":true}")))
+    );
+}
+
+#[test]
+fn script_not_installed_test() {
+    let mut scripts = vec!["#!/bash\necho hi"];
+    if cfg!(not(feature = "python")) {
+        scripts.push("#!/python\nprint('hi')");
+    }
+    for script_s in scripts {
+        match script(&CommonContent {
+            content: Some(serde_json::Value::String(String::from(script_s))),
+            env: None,
+            ..CommonContent::default()
+        }) {
+            Err(VermanSchemaError::NotInstalled(_)) => {}
+            other => panic!("expected `NotInstalled` got {:?}", other),
+        }
+    }
+}
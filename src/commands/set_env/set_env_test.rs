@@ -8,6 +8,7 @@ fn set_env_test() {
         env: Some(indexmap::indexmap! {
             String::from("ENV WAS SET") => serde_json::Value::String(String::from("indeed"))
         }),
+        ..CommonContent::default()
     };
     let common_output = set_env(&common_input).unwrap();
     assert_eq!(common_output, common_input);
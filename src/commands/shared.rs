@@ -37,6 +37,7 @@ pub(crate) fn interpolate_input_with_env(
         Ok(CommonContent {
             content: Some(serde_json::Value::String(String::from(substituted))),
             env: Some(env.clone()),
+            ..CommonContent::default()
         })
     };
     match content {
@@ -81,6 +82,7 @@ pub(crate) fn interpolate_input_else_get_prior_output(
                 VermanSchemaError::NotFound(_) => CommonContent {
                     env: common_content.env.clone(),
                     content: None,
+                    ..CommonContent::default()
                 },
                 err @ _ => return Err(err),
             },
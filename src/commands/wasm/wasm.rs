@@ -0,0 +1,93 @@
+use crate::commands::command::CommandKey;
+use crate::errors::VermanSchemaError;
+use crate::models::{CommonContent, WasmCommandArgs};
+
+/// Instructions budget when `WasmCommandArgs.fuel` is unset
+pub const DEFAULT_FUEL: u64 = 1_000_000_000;
+
+/// Instructions budget per millisecond of `CommonContent.timeout_ms`, see `fuel_budget`
+pub const FUEL_PER_MS: u64 = 1_000_000;
+
+/// Run the local `.wasm` module at `WasmCommandArgs.path` with `CMD_PREVIOUS_CONTENT` as input.
+///
+/// The module has no imports and must export:
+/// - `memory`
+/// - `alloc(len: i32) -> i32`, returning where the host writes `len` bytes of input JSON
+/// - `run(ptr: i32, len: i32) -> i64`, returning `(out_ptr << 32) | out_len` of its output JSON
+///
+/// The output JSON becomes `content`.
+/// Execution stops once `fuel` is consumed.
+/// wasmi cannot interrupt a running call, so with `common_content.timeout_ms` the fuel budget is
+/// derived from it (see `fuel_budget`): once `Task::process` stopped waiting for the module, it
+/// stops soon after instead of burning the rest of `fuel`.
+pub fn wasm(args: &WasmCommandArgs) -> Result<CommonContent, VermanSchemaError> {
+    let bytes = std::fs::read(&args.path)?;
+    let input = serde_json::to_vec(
+        args.common_content
+            .env
+            .as_ref()
+            .and_then(|env| env.get(CommandKey::PreviousContent.to_string().as_str()))
+            .unwrap_or(&serde_json::Value::Null),
+    )?;
+    let fuel = fuel_budget(args);
+    let output = run_module(&bytes, &input, fuel).map_err(VermanSchemaError::WasmError)?;
+    Ok(CommonContent {
+        content: Some(serde_json::from_slice(&output)?),
+        env: args.common_content.env.to_owned(),
+        ..CommonContent::default()
+    })
+}
+
+/// `fuel` (`DEFAULT_FUEL` when unset), capped at `FUEL_PER_MS` per millisecond of
+/// `common_content.timeout_ms`
+fn fuel_budget(args: &WasmCommandArgs) -> u64 {
+    let fuel = args.fuel.unwrap_or(DEFAULT_FUEL);
+    match args.common_content.timeout_ms {
+        Some(timeout_ms) => fuel.min(timeout_ms.saturating_mul(FUEL_PER_MS)),
+        None => fuel,
+    }
+}
+
+/// Errors are `String`s so that this can run on another thread
+fn run_module(bytes: &[u8], input: &[u8], fuel: u64) -> Result<Vec<u8>, String> {
+    let mut config = wasmi::Config::default();
+    config.consume_fuel(true);
+    let engine = wasmi::Engine::new(&config);
+    let module = wasmi::Module::new(&engine, bytes).map_err(wasm_error)?;
+    let mut store = wasmi::Store::new(&engine, ());
+    store.set_fuel(fuel).map_err(wasm_error)?;
+    let instance = wasmi::Linker::<()>::new(&engine)
+        .instantiate(&mut store, &module)
+        .map_err(wasm_error)?
+        .start(&mut store)
+        .map_err(wasm_error)?;
+    let memory = instance
+        .get_memory(&store, "memory")
+        .ok_or("missing export `memory`")?;
+    let alloc = instance
+        .get_typed_func::<i32, i32>(&store, "alloc")
+        .map_err(wasm_error)?;
+    let run = instance
+        .get_typed_func::<(i32, i32), i64>(&store, "run")
+        .map_err(wasm_error)?;
+
+    let len = i32::try_from(input.len()).map_err(wasm_error)?;
+    let ptr = alloc.call(&mut store, len).map_err(wasm_error)?;
+    memory
+        .write(&mut store, ptr as u32 as usize, input)
+        .map_err(wasm_error)?;
+    let packed = run.call(&mut store, (ptr, len)).map_err(wasm_error)? as u64;
+    let mut output = vec![0u8; (packed & 0xFFFF_FFFF) as usize];
+    memory
+        .read(&store, (packed >> 32) as usize, &mut output)
+        .map_err(wasm_error)?;
+    Ok(output)
+}
+
+fn wasm_error(error: impl std::fmt::Display) -> String {
+    error.to_string()
+}
+
+#[cfg(test)]
+#[path = "wasm_test.rs"]
+mod tests;
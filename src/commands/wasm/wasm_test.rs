@@ -0,0 +1,80 @@
+use super::*;
+use crate::models::{CommonContent, WasmCommandArgs};
+
+/// Echoes its input back: `alloc` hands out offset 1024, `run` returns the same bytes
+const ECHO_WAT: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+  (func (export "run") (param $ptr i32) (param $len i32) (result i64)
+    (i64.or
+      (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+      (i64.extend_i32_u (local.get $len)))))
+"#;
+
+const LOOP_WAT: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (func (export "alloc") (param i32) (result i32) (i32.const 0))
+  (func (export "run") (param i32 i32) (result i64)
+    (loop $forever (br $forever))
+    (i64.const 0)))
+"#;
+
+fn write_module(wat: &str, name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("verman_{}_{}.wasm", name, std::process::id()));
+    std::fs::write(&path, wat::parse_str(wat).unwrap()).unwrap();
+    path.display().to_string()
+}
+
+fn args(path: String) -> WasmCommandArgs {
+    WasmCommandArgs {
+        path,
+        fuel: None,
+        common_content: CommonContent {
+            content: None,
+            env: Some(indexmap::indexmap! {
+                CommandKey::PreviousContent.to_string() => serde_json::json!({"a": [1, 2]}),
+            }),
+            ..CommonContent::default()
+        },
+    }
+}
+
+#[test]
+fn wasm_echo_test() {
+    let common_output = wasm(&args(write_module(ECHO_WAT, "echo"))).unwrap();
+    assert_eq!(
+        common_output.content,
+        Some(serde_json::json!({"a": [1, 2]}))
+    );
+}
+
+#[test]
+fn wasm_fuel_test() {
+    assert!(matches!(
+        wasm(&WasmCommandArgs {
+            fuel: Some(10_000),
+            ..args(write_module(LOOP_WAT, "loop"))
+        }),
+        Err(VermanSchemaError::WasmError(_))
+    ));
+}
+
+#[test]
+fn wasm_timeout_test() {
+    let mut loop_args = args(write_module(LOOP_WAT, "loop_timeout"));
+    loop_args.common_content.timeout_ms = Some(10);
+    assert_eq!(fuel_budget(&loop_args), 10 * FUEL_PER_MS);
+    assert_eq!(
+        fuel_budget(&WasmCommandArgs {
+            fuel: Some(5),
+            ..loop_args.clone()
+        }),
+        5
+    );
+    assert!(matches!(
+        wasm(&loop_args),
+        Err(VermanSchemaError::WasmError(_))
+    ));
+}
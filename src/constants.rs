@@ -0,0 +1,63 @@
+use crate::verman_schema::Os;
+
+/// Predefined constants, accessible with `${}` syntax; see README.md
+#[derive(derive_more::Display)]
+pub enum ConstantKey {
+    #[display("ARCH")]
+    Arch,
+
+    #[display("FAMILY")]
+    Family,
+
+    #[display("OS")]
+    Os,
+
+    #[display("BUILD_TIME")]
+    BuildTime,
+}
+
+/// When this crate was built, set by `build.rs`
+pub fn build_time() -> std::time::SystemTime {
+    std::time::UNIX_EPOCH
+        + std::time::Duration::from_secs(env!("VERMAN_BUILD_TIME").parse().unwrap_or_default())
+}
+
+/// `BUILD_TIME` is given in seconds since the UNIX epoch
+pub fn predefined_constants() -> indexmap::IndexMap<String, serde_json::Value> {
+    indexmap::indexmap! {
+        ConstantKey::Arch.to_string() => serde_json::Value::String(String::from(std::env::consts::ARCH)),
+        ConstantKey::Family.to_string() => serde_json::Value::String(String::from(std::env::consts::FAMILY)),
+        ConstantKey::Os.to_string() => serde_json::Value::String(String::from(std::env::consts::OS)),
+        ConstantKey::BuildTime.to_string() => serde_json::Value::from(
+            build_time()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default()
+        ),
+    }
+}
+
+impl Os {
+    pub fn current() -> Self {
+        Self::from(std::env::consts::OS)
+    }
+}
+
+/// From the values of `std::env::consts::OS`
+impl From<&str> for Os {
+    fn from(os: &str) -> Self {
+        match os {
+            "linux" => Self::Linux,
+            "macos" => Self::Macos,
+            "ios" => Self::Ios,
+            "freebsd" => Self::Freebsd,
+            "dragonfly" => Self::Dragonfly,
+            "netbsd" => Self::Netbsd,
+            "openbsd" => Self::Openbsd,
+            "solaris" => Self::Solaris,
+            "android" => Self::Android,
+            "windows" => Self::Windows,
+            _ => Self::Unspecified,
+        }
+    }
+}
@@ -8,6 +8,88 @@ pub enum VermanSchemaError {
     #[display("NotFound({_0:#?})")]
     NotFound(&'static str) = 404,
 
+    #[error(ignore)]
+    #[from(skip)]
+    #[display("InvalidRoute({value:?} of service {service:?})")]
+    InvalidRoute { service: String, value: String } = 581,
+
+    #[error(ignore)]
+    #[from(skip)]
+    #[display("InvalidHost({_0:?})")]
+    InvalidHost(String) = 582,
+
+    #[error(ignore)]
+    #[from(skip)]
+    #[display("NoCandidateVersions({kind:?}, {constraints:?})")]
+    NoCandidateVersions {
+        kind: String,
+        constraints: Vec<String>,
+    } = 583,
+
+    #[error(ignore)]
+    #[from(skip)]
+    #[display("UnexpectedExitCode({program:?} exited with {actual}, expected {expected})")]
+    UnexpectedExitCode {
+        program: String,
+        actual: i32,
+        expected: i32,
+    } = 584,
+
+    #[error(ignore)]
+    #[from(skip)]
+    #[display("Timeout({_0}ms)")]
+    Timeout(u64) = 585,
+
+    #[error(ignore)]
+    #[from(skip)]
+    #[display("PipelineValidation({pipeline:?}, {diagnostics:#?})")]
+    PipelineValidation {
+        pipeline: String,
+        diagnostics: Vec<Diagnostic>,
+    } = 586,
+
+    #[error(ignore)]
+    #[from(skip)]
+    #[display("UnknownMountAction({action:?} at {location:?})")]
+    UnknownMountAction { action: String, location: String } = 587,
+
+    #[error(ignore)]
+    #[from(skip)]
+    #[display("InvalidExpression({expression:?} at {position}: {message})")]
+    InvalidExpression {
+        expression: String,
+        position: usize,
+        message: String,
+    } = 588,
+
+    #[error(ignore)]
+    #[from(skip)]
+    #[display("NoHealthyServer({kind:?}, tried {tried:?})")]
+    NoHealthyServer { kind: String, tried: Vec<String> } = 589,
+
+    #[error(ignore)]
+    #[from(skip)]
+    #[display("VersionConflict({kind:?}, {constraints:?})")]
+    VersionConflict {
+        kind: String,
+        constraints: Vec<String>,
+    } = 590,
+
+    #[error(ignore)]
+    #[from(skip)]
+    #[display("UnknownKind({_0:?})")]
+    UnknownKind(String) = 591,
+
+    #[error(ignore)]
+    #[from(skip)]
+    #[display("UnresolvedRef({reference:?} at {location:?})")]
+    UnresolvedRef { reference: String, location: String } = 592,
+
+    #[error(ignore)]
+    #[from(skip)]
+    #[display("CyclicRef({_0:?})")]
+    CyclicRef(Vec<String>) = 593,
+
     #[error(ignore)]
     #[from(skip)]
     #[display("{_0:?}")]
@@ -30,6 +112,14 @@ pub enum VermanSchemaError {
     #[display("{_0:?}")]
     NotInstalled(String) = 598,
 
+    #[error(ignore)]
+    #[from(skip)]
+    #[display("SchemaValidation({location:?}, {violations:#?})")]
+    SchemaValidation {
+        location: String,
+        violations: Vec<SchemaViolation>,
+    } = 599,
+
     // ************************
     // * Library level errors *
     // ************************
@@ -49,6 +139,19 @@ pub enum VermanSchemaError {
     #[display("`serde_yaml::Error` error. {error:?}")]
     SerdeYamlError { error: serde_yaml::Error } = 722,
 
+    #[display("`toml::ser::Error` error. {error:?}")]
+    TomlSerError { error: toml::ser::Error } = 723,
+
+    #[error(ignore)]
+    #[from(skip)]
+    #[display("{path}:{line}:{column}: {message}")]
+    Deserialize {
+        path: String,
+        line: usize,
+        column: usize,
+        message: String,
+    } = 724,
+
     #[display("`reqwest::Error` error. {error:?}")]
     ReqwestError { error: reqwest::Error } = 732,
 
@@ -91,6 +194,54 @@ pub enum VermanSchemaError {
     #[from(skip)]
     #[display("`jaq` str error. {_0}")]
     JaqStrError(String) = 742,
+
+    #[error(ignore)]
+    #[from(skip)]
+    #[display("`jsonschema` error. {_0}")]
+    JsonSchemaError(String) = 743,
+
+    #[display("`semver::Error` error. {error:?}")]
+    SemverError { error: semver::Error } = 744,
+
+    #[cfg(feature = "lua")]
+    #[display("`mlua::Error` error. {error:?}")]
+    LuaError { error: mlua::Error } = 750,
+
+    #[cfg(feature = "js")]
+    #[error(ignore)]
+    #[from(skip)]
+    #[display("`rquickjs` error. {_0}")]
+    JsError(String) = 751,
+
+    #[cfg(feature = "wasm")]
+    #[error(ignore)]
+    #[from(skip)]
+    #[display("`wasmi` error. {_0}")]
+    WasmError(String) = 752,
+
+    #[cfg(feature = "python")]
+    #[error(ignore)]
+    #[from(skip)]
+    #[display("`pyo3` error. {message}\n{traceback}")]
+    PythonError { message: String, traceback: String } = 753,
+
+    #[cfg(feature = "tls")]
+    #[display("`rcgen::Error` error. {error:?}")]
+    RcgenError { error: rcgen::Error } = 754,
+}
+
+/// One failed JSON Schema keyword, located by the JSON pointer of the offending instance
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchemaViolation {
+    pub instance_path: String,
+    pub message: String,
+}
+
+/// One problem found by `Pipeline::validate`, located by a JSON pointer into the `Pipeline`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub location: String,
+    pub message: String,
 }
 
 impl VermanSchemaError {
@@ -101,8 +252,16 @@ impl VermanSchemaError {
 
 impl std::process::Termination for VermanSchemaError {
     fn report(self) -> std::process::ExitCode {
-        if let VermanSchemaError::ExitCode(exit_code) = self {
-            return exit_code;
+        match self {
+            VermanSchemaError::ExitCode(exit_code) => return exit_code,
+            /* `0` (when another code was expected), signals and codes out of range still fail */
+            VermanSchemaError::UnexpectedExitCode { actual, .. } => {
+                return match u8::try_from(actual) {
+                    Ok(code) if code != 0 => std::process::ExitCode::from(code),
+                    _ => std::process::ExitCode::FAILURE,
+                }
+            }
+            _ => {}
         }
         let status_code = self.discriminant();
         if status_code > u8::MAX as u16 {
@@ -0,0 +1,84 @@
+use crate::commands::command::Command;
+use crate::errors::VermanSchemaError;
+use crate::models::{Pipeline, Task};
+use crate::verman_schema::Root;
+
+/// Types with a JSON Schema; see `json_schema`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, derive_more::Display)]
+pub enum SchemaKind {
+    #[display("pipeline")]
+    Pipeline,
+
+    #[display("task")]
+    Task,
+
+    #[display("command")]
+    Command,
+
+    #[display("root")]
+    Root,
+}
+
+impl SchemaKind {
+    pub const ALL: [Self; 4] = [Self::Pipeline, Self::Task, Self::Command, Self::Root];
+
+    /// Name of the artifact under `schema/`, e.g., `pipeline.schema.json`
+    pub fn file_name(&self) -> String {
+        format!("{}.schema.json", self)
+    }
+}
+
+impl std::str::FromStr for SchemaKind {
+    type Err = VermanSchemaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.to_string() == s.to_ascii_lowercase())
+            .ok_or(VermanSchemaError::NotFound(
+                "schema, expected one of `pipeline`, `task`, `command`, `root`",
+            ))
+    }
+}
+
+/// JSON Schema (draft 7) of `kind`, e.g., for editors to autocomplete pipeline files.
+/// `Command` is tagged by `cmd`; its feature-gated variants are only present when enabled.
+pub fn json_schema(kind: SchemaKind) -> serde_json::Value {
+    let root_schema = match kind {
+        SchemaKind::Pipeline => schemars::schema_for!(Pipeline),
+        SchemaKind::Task => schemars::schema_for!(Task),
+        SchemaKind::Command => schemars::schema_for!(Command),
+        SchemaKind::Root => schemars::schema_for!(Root),
+    };
+    serde_json::to_value(root_schema).unwrap()
+}
+
+/// Header values of `HttpArgs.headers`: `serde_json_extensions::ValueNoObjOrArr`
+pub(crate) struct Scalar;
+
+impl schemars::JsonSchema for Scalar {
+    fn schema_name() -> String {
+        String::from("Scalar")
+    }
+
+    fn json_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        use schemars::schema::InstanceType;
+        schemars::schema::SchemaObject {
+            instance_type: Some(
+                vec![
+                    InstanceType::String,
+                    InstanceType::Number,
+                    InstanceType::Boolean,
+                    InstanceType::Null,
+                ]
+                .into(),
+            ),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+#[cfg(test)]
+#[path = "json_schema_test.rs"]
+mod tests;
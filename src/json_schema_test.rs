@@ -0,0 +1,52 @@
+use super::*;
+
+#[test]
+fn command_schema_cmd_tag_test() {
+    let schema = json_schema(SchemaKind::Command);
+    let tags: Vec<&str> = schema["oneOf"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|variant| variant["properties"]["cmd"]["enum"][0].as_str())
+        .collect();
+    assert!(tags.contains(&"Echo") && tags.contains(&"HttpClient") && tags.contains(&"Exec"));
+}
+
+#[test]
+fn pipeline_schema_validates_pipeline_test() {
+    let validator = jsonschema::validator_for(&json_schema(SchemaKind::Pipeline)).unwrap();
+    let pipeline = serde_json::json!({
+        "name": "p", "version": "0.0.1", "description": "", "url": "", "engine_version": "0.1.0",
+        "tasks": {"greet": {"commands": [{"cmd": "Echo", "content": "hi"}]}}
+    });
+    assert!(validator.is_valid(&pipeline));
+    let mut unknown_cmd = pipeline.clone();
+    unknown_cmd["tasks"]["greet"]["commands"][0]["cmd"] = serde_json::json!("Nope");
+    assert!(!validator.is_valid(&unknown_cmd));
+}
+
+/// `schema/*.schema.json` are generated with default features; regenerate them with
+/// `VERMAN_UPDATE_SCHEMA=1 cargo test json_schema_artifacts`
+#[cfg(not(any(feature = "js", feature = "lua", feature = "python", feature = "wasm")))]
+#[test]
+fn json_schema_artifacts_test() {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("schema");
+    for kind in SchemaKind::ALL {
+        let path = dir.join(kind.file_name());
+        let generated = format!(
+            "{}\n",
+            serde_json::to_string_pretty(&json_schema(kind)).unwrap()
+        );
+        if std::env::var_os("VERMAN_UPDATE_SCHEMA").is_some() {
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(&path, generated).unwrap();
+        } else {
+            assert_eq!(
+                std::fs::read_to_string(&path).unwrap_or_default(),
+                generated,
+                "{} is stale, regenerate with `VERMAN_UPDATE_SCHEMA=1 cargo test json_schema_artifacts`",
+                path.display()
+            );
+        }
+    }
+}
@@ -6,6 +6,13 @@ extern crate core;
 
 pub mod models;
 
+pub mod constants;
+
+pub mod system_env;
+
+#[path = "loader/lib.rs"]
+pub mod loader;
+
 pub mod verman_schema;
 
 #[path = "task/lib.rs"]
@@ -13,10 +20,20 @@ pub mod task;
 
 #[path = "commands/lib.rs"]
 pub mod commands;
-mod errors;
+pub mod errors;
 
 #[path = "pipeline/lib.rs"]
 pub mod pipeline;
 
+#[path = "scheduler/lib.rs"]
+pub mod scheduler;
+
+pub mod json_schema;
+
+pub(crate) mod schema_validation;
+
+#[path = "stack/lib.rs"]
+pub mod stack;
+
 #[cfg(test)]
 mod test_models;
@@ -0,0 +1,150 @@
+use crate::errors::VermanSchemaError;
+use crate::models::Pipeline;
+
+const INLINE_PATH: &str = "<inline>";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, derive_more::Display)]
+pub enum Format {
+    #[display("json")]
+    Json,
+
+    #[display("toml")]
+    Toml,
+
+    #[display("yaml")]
+    Yaml,
+}
+
+impl Format {
+    pub fn from_extension(path: &std::path::Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+
+    /// JSON if it looks like an object, else TOML if it parses as TOML, else YAML
+    pub fn sniff(s: &str) -> Self {
+        if s.trim_start().starts_with('{') {
+            Self::Json
+        } else if toml::from_str::<toml::Table>(s).is_ok() {
+            Self::Toml
+        } else {
+            Self::Yaml
+        }
+    }
+}
+
+impl std::str::FromStr for Format {
+    type Err = VermanSchemaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "toml" => Ok(Self::Toml),
+            "yaml" | "yml" => Ok(Self::Yaml),
+            _ => Err(VermanSchemaError::NotFound(
+                "format, expected one of `json`, `toml`, `yaml`",
+            )),
+        }
+    }
+}
+
+/// Read `path` picking the `Format` from its extension, else by sniffing its content
+pub fn from_path<T: serde::de::DeserializeOwned>(
+    path: impl AsRef<std::path::Path>,
+) -> Result<T, VermanSchemaError> {
+    let path = path.as_ref();
+    let s = std::fs::read_to_string(path)?;
+    let format = Format::from_extension(path).unwrap_or_else(|| Format::sniff(&s));
+    deserialize(&s, format, &path.display().to_string())
+}
+
+pub fn from_str_with_format<T: serde::de::DeserializeOwned>(
+    s: &str,
+    format: Format,
+) -> Result<T, VermanSchemaError> {
+    deserialize(s, format, INLINE_PATH)
+}
+
+pub fn to_string_with_format<T: serde::Serialize>(
+    value: &T,
+    format: Format,
+) -> Result<String, VermanSchemaError> {
+    Ok(match format {
+        Format::Json => serde_json::to_string_pretty(value)?,
+        Format::Toml => toml::to_string(value)?,
+        Format::Yaml => serde_yaml::to_string(value)?,
+    })
+}
+
+fn deserialize<T: serde::de::DeserializeOwned>(
+    s: &str,
+    format: Format,
+    path: &str,
+) -> Result<T, VermanSchemaError> {
+    let (line, column, message) = match format {
+        Format::Json => match serde_json::from_str(s) {
+            Ok(value) => return Ok(value),
+            Err(error) => (error.line(), error.column(), error.to_string()),
+        },
+        Format::Toml => match toml::from_str(s) {
+            Ok(value) => return Ok(value),
+            Err(error) => {
+                let (line, column) = error
+                    .span()
+                    .map(|span| line_column_of_offset(s, span.start))
+                    .unwrap_or((0, 0));
+                (line, column, error.message().to_string())
+            }
+        },
+        Format::Yaml => match serde_yaml::from_str(s) {
+            Ok(value) => return Ok(value),
+            Err(error) => {
+                let (line, column) = error
+                    .location()
+                    .map(|location| (location.line(), location.column()))
+                    .unwrap_or((0, 0));
+                (line, column, error.to_string())
+            }
+        },
+    };
+    let location_suffix = format!(" at line {} column {}", line, column);
+    Err(VermanSchemaError::Deserialize {
+        path: path.to_string(),
+        line,
+        column,
+        message: message
+            .strip_suffix(location_suffix.as_str())
+            .unwrap_or(message.as_str())
+            .to_string(),
+    })
+}
+
+/// 1-based line and column of the byte `offset` into `s`
+fn line_column_of_offset(s: &str, offset: usize) -> (usize, usize) {
+    let before = &s[..offset.min(s.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.chars().rev().take_while(|c| *c != '\n').count() + 1;
+    (line, column)
+}
+
+impl Pipeline {
+    pub fn from_path(path: impl AsRef<std::path::Path>) -> Result<Self, VermanSchemaError> {
+        from_path(path)
+    }
+
+    pub fn from_str_with_format(s: &str, format: Format) -> Result<Self, VermanSchemaError> {
+        from_str_with_format(s, format)
+    }
+
+    pub fn to_string_with_format(&self, format: Format) -> Result<String, VermanSchemaError> {
+        to_string_with_format(self, format)
+    }
+}
+
+#[cfg(test)]
+#[path = "loader_test.rs"]
+mod tests;
@@ -0,0 +1,78 @@
+use super::*;
+use crate::commands::command::Command;
+use crate::models::{CommonContent, Task};
+
+lazy_static::lazy_static! {
+    static ref PIPELINE0: Pipeline = Pipeline {
+        name: String::from("loader"),
+        version: String::from("0.0.1"),
+        tasks: Some(indexmap::indexmap! {
+            String::from("task0") => Task {
+                commands: vec![Command::Echo(CommonContent {
+                    content: Some(serde_json::Value::String(String::from("hi ${WHO}"))),
+                    env: Some(indexmap::indexmap! {
+                        String::from("WHO") => serde_json::Value::String(String::from("there"))
+                    }),
+                    ..CommonContent::default()
+                })],
+                ..Task::default()
+            }
+        }),
+        ..Pipeline::default()
+    };
+}
+
+#[test]
+fn roundtrip_every_format_test() {
+    for format in [Format::Json, Format::Toml, Format::Yaml] {
+        let s = PIPELINE0.to_string_with_format(format).unwrap();
+        assert_eq!(Format::sniff(&s), format, "{}", s);
+        assert_eq!(
+            Pipeline::from_str_with_format(&s, format).unwrap(),
+            *PIPELINE0
+        );
+    }
+}
+
+#[test]
+fn from_path_test() {
+    let dir = std::env::temp_dir().join(format!("verman-loader-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    for (file_name, format) in [
+        ("pipeline.json", Format::Json),
+        ("pipeline.toml", Format::Toml),
+        ("pipeline.yml", Format::Yaml),
+        ("pipeline", Format::Toml),
+    ] {
+        let path = dir.join(file_name);
+        std::fs::write(&path, PIPELINE0.to_string_with_format(format).unwrap()).unwrap();
+        assert_eq!(Pipeline::from_path(&path).unwrap(), *PIPELINE0);
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn format_from_str_test() {
+    assert_eq!("YML".parse::<Format>().unwrap(), Format::Yaml);
+    assert!("xml".parse::<Format>().is_err());
+}
+
+#[test]
+fn deserialize_error_location_test() {
+    let cases = [
+        (Format::Json, "{\n  \"name\": \"x\",\n  \"nope\": 5\n}", (3, 8)),
+        (Format::Toml, "name = \"x\"\nversion = \n", (2, 11)),
+        (Format::Yaml, "name: x\nversion: [\n", (2, 10)),
+    ];
+    for (format, s, (expected_line, expected_column)) in cases {
+        match Pipeline::from_str_with_format(s, format) {
+            Err(VermanSchemaError::Deserialize {
+                path, line, column, ..
+            }) => {
+                assert_eq!(path, "<inline>");
+                assert_eq!((line, column), (expected_line, expected_column), "{}", format);
+            }
+            other => panic!("expected `Deserialize` got {:?}", other),
+        }
+    }
+}
@@ -2,8 +2,9 @@ use std::fmt::Debug;
 use std::str::FromStr;
 
 use crate::commands::command::Command;
+use crate::errors::VermanSchemaError;
 
-#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Debug, PartialEq, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct Pipeline {
     pub name: String,
@@ -16,7 +17,11 @@ pub struct Pipeline {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub env: Option<indexmap::IndexMap<String, serde_json::Value>>,
 
-    /// List of pipeline stages
+    /// Opt-in inheritance of the process environment ; not inherited when `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inherit_env: Option<InheritEnv>,
+
+    /// List of pipeline stages ; when set, takes priority over `tasks` for execution
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pipe: Option<Vec<Stage>>,
 
@@ -29,17 +34,40 @@ pub struct Pipeline {
     pub schemas: Option<std::collections::HashMap<String, JsonSchema>>,
 }
 
-#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+/// Which process environment variables are inherited, see README.md "Resolving configuration"
+#[derive(
+    Clone, Debug, Default, PartialEq, schemars::JsonSchema, serde::Deserialize, serde::Serialize,
+)]
+#[serde(deny_unknown_fields)]
+pub struct InheritEnv {
+    /// Inherit these variables ; when neither `allow` nor `prefixes` is set, everything is
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow: Option<Vec<String>>,
+
+    /// Inherit variables whose name starts with one of these
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefixes: Option<Vec<String>>,
+
+    /// Never inherit these variables ; takes priority over `allow` and `prefixes`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deny: Option<Vec<String>>,
+}
+
+#[derive(
+    Clone, Debug, Default, PartialEq, schemars::JsonSchema, serde::Deserialize, serde::Serialize,
+)]
 #[serde(deny_unknown_fields)]
 pub struct Stage {
     pub name: String,
-    /// List of dependencies for this stage
-    pub deps: Vec<Task>,
+    /// List of dependencies for this stage ; `{"$ref": "#/tasks/name"}` refers to `Pipeline.tasks`
+    pub deps: Vec<TaskOrRef>,
     /// Whether `Task`s in this stage should run sequentially
     pub sequential: bool,
 }
 
-#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[derive(
+    Clone, Debug, Default, PartialEq, schemars::JsonSchema, serde::Deserialize, serde::Serialize,
+)]
 #[serde(deny_unknown_fields)]
 pub struct Task {
     pub commands: Vec<Command>,
@@ -52,15 +80,90 @@ pub struct Task {
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub env: Option<indexmap::IndexMap<String, serde_json::Value>>,
+
+    /// Names of tasks that must finish before this one starts (on top of the ones inferred
+    /// from `${pipeline__task_CMD_CONTENT}` references)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub depends_on: Option<Vec<String>>,
+
+    /// Retry policy of its `Command`s, unless they set their own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<Retry>,
+
+    /// Wall-clock limit in milliseconds of the whole `Task`, i.e., of all its `Command`s and their
+    /// retries; see `CommonContent.timeout_ms` for a limit per `Command`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+
+    /// Whether its `Command`s that fail are skipped, unless they set their own; the error is
+    /// then in `CMD_LAST_ERROR`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub continue_on_error: Option<bool>,
+}
+
+/// When and how often a failing `Command` is run again
+#[derive(
+    Clone, Debug, Default, PartialEq, schemars::JsonSchema, serde::Deserialize, serde::Serialize,
+)]
+#[serde(deny_unknown_fields)]
+pub struct Retry {
+    /// Attempts after the first one
+    pub count: u32,
+
+    /// Delay in milliseconds before the first retry, doubled before each of the next ones
+    #[serde(default)]
+    pub backoff_ms: u64,
+
+    /// Only retry HTTP errors with these status codes; errors without one (e.g., timeouts) are
+    /// always retried
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_on: Option<Vec<u16>>,
+}
+
+impl Retry {
+    pub fn should_retry(&self, error: &VermanSchemaError) -> bool {
+        match (&self.retry_on, error) {
+            (
+                Some(status_codes),
+                VermanSchemaError::HttpError(status_code)
+                | VermanSchemaError::HttpErrorWithBody(status_code, _),
+            ) => status_codes.contains(status_code),
+            _ => true,
+        }
+    }
 }
 
 pub type JsonSchema = serde_json::Value;
 
+/// [JSON-reference](https://datatracker.ietf.org/doc/html/draft-pbryan-zyp-json-ref-03),
+/// e.g., `{"$ref": "#/tasks/bar"}`
+#[derive(Clone, Debug, PartialEq, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct JsonRef {
+    #[serde(rename = "$ref")]
+    pub reference: String,
+}
+
+#[derive(Clone, Debug, PartialEq, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
+#[serde(untagged)]
+pub enum TaskOrRef {
+    Ref(JsonRef),
+    Task(Task),
+}
+
+impl From<Task> for TaskOrRef {
+    fn from(task: Task) -> Self {
+        Self::Task(task)
+    }
+}
+
 /********************
  * Common `struct`s *
  ********************/
 
-#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[derive(
+    Clone, Debug, Default, PartialEq, schemars::JsonSchema, serde::Deserialize, serde::Serialize,
+)]
 #[serde(deny_unknown_fields)]
 pub struct CommonContent {
     /// If `-` provided (default) then stdin / output from previous task is read
@@ -69,26 +172,45 @@ pub struct CommonContent {
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub env: Option<indexmap::IndexMap<String, serde_json::Value>>,
+
+    /// Overrides `Task.retry`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<Retry>,
+
+    /// Wall-clock limit in milliseconds of each attempt of this `Command`.
+    /// Synchronous `Command`s then run on their own thread, which keeps running within their own
+    /// limits once timed out (e.g., `commands::js::TIMEOUT`); `Wasm` derives its fuel from it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+
+    /// Overrides `Task.continue_on_error`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub continue_on_error: Option<bool>,
 }
 
 /******************
  * HTTP `struct`s *
  ******************/
 
-#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[derive(
+    Clone, Debug, Default, PartialEq, schemars::JsonSchema, serde::Deserialize, serde::Serialize,
+)]
 #[serde(deny_unknown_fields)]
 pub struct HttpArgs {
     #[serde(
         deserialize_with = "de_http__uri__Uri",
         serialize_with = "ser_http___uri__Uri"
     )]
+    #[schemars(with = "String")]
     pub url: http::uri::Uri,
     #[serde(
         deserialize_with = "de_http__method__Method",
         serialize_with = "ser_http__method__Method"
     )]
+    #[schemars(with = "String")]
     pub method: http::method::Method,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<Vec<indexmap::IndexMap<String, crate::json_schema::Scalar>>>")]
     pub headers: Option<Vec<indexmap::IndexMap<String, serde_json_extensions::ValueNoObjOrArr>>>,
 }
 
@@ -130,7 +252,9 @@ where
     s.serialize_str(method.to_string().as_str())
 }
 
-#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[derive(
+    Clone, Debug, Default, PartialEq, schemars::JsonSchema, serde::Deserialize, serde::Serialize,
+)]
 #[serde(deny_unknown_fields)]
 pub struct HttpCommandArgs<T = serde_json::Value> {
     pub args: HttpArgs,
@@ -156,14 +280,60 @@ impl HttpCommandArgs {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[derive(
+    Clone, Debug, Default, PartialEq, schemars::JsonSchema, serde::Deserialize, serde::Serialize,
+)]
+#[serde(deny_unknown_fields)]
+pub struct ExecArgs {
+    pub program: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Working directory; defaults to the current one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+}
+
+/// Arguments of `Command::Exec`
+#[derive(
+    Clone, Debug, Default, PartialEq, schemars::JsonSchema, serde::Deserialize, serde::Serialize,
+)]
+#[serde(deny_unknown_fields)]
+pub struct ExecCommandArgs {
+    pub args: ExecArgs,
+    #[serde(default)]
+    pub common_content: CommonContent,
+    #[serde(default)]
+    pub expectation: Expectation,
+}
+
+/// Arguments of `Command::Wasm`
+#[derive(
+    Clone, Debug, Default, PartialEq, schemars::JsonSchema, serde::Deserialize, serde::Serialize,
+)]
+#[serde(deny_unknown_fields)]
+pub struct WasmCommandArgs {
+    /// Local path to the `.wasm` module
+    pub path: String,
+
+    /// Instructions budget; defaults to `commands::wasm::DEFAULT_FUEL`, capped by
+    /// `common_content.timeout_ms`, see `commands::wasm::FUEL_PER_MS`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fuel: Option<u64>,
+
+    #[serde(default)]
+    pub common_content: CommonContent,
+}
+
+#[derive(Clone, Debug, PartialEq, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct Expectation {
     pub status_code: u16,
     pub exit_code: i32,
 }
 
-#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[derive(
+    Clone, Debug, Default, PartialEq, schemars::JsonSchema, serde::Deserialize, serde::Serialize,
+)]
 #[serde(deny_unknown_fields)]
 pub struct ExpectationHttpClient {
     pub status_code: u16,
@@ -0,0 +1,151 @@
+use crate::errors::VermanSchemaError;
+use crate::models::{JsonRef, JsonSchema, Pipeline, Stage, Task, TaskOrRef};
+
+const SCHEMAS_PREFIX: &str = "#/schemas/";
+const TASKS_PREFIX: &str = "#/tasks/";
+
+impl Pipeline {
+    /// Copy of this `Pipeline` with `{"$ref": "#/schemas/…"}` (anywhere inside `input_schema` or
+    /// `output_schema`) and `{"$ref": "#/tasks/…"}` (inside `Stage.deps`) inlined.
+    /// Other `$ref`s, e.g., `#/definitions/…`, are left for the JSON Schema validator.
+    pub fn resolve_refs(&self) -> Result<Pipeline, VermanSchemaError> {
+        let resolver = Resolver {
+            root: serde_json::json!({ "schemas": self.schemas }),
+        };
+        let tasks = match &self.tasks {
+            Some(tasks) => Some(
+                tasks
+                    .iter()
+                    .map(|(name, task)| {
+                        let location = format!("{}{}", TASKS_PREFIX, escape(name));
+                        Ok((name.to_owned(), resolver.resolve_task(task, &location)?))
+                    })
+                    .collect::<Result<indexmap::IndexMap<String, Task>, VermanSchemaError>>()?,
+            ),
+            None => None,
+        };
+        let pipe = match &self.pipe {
+            Some(stages) => Some(
+                stages
+                    .iter()
+                    .enumerate()
+                    .map(|(i, stage)| {
+                        Ok(Stage {
+                            deps: stage
+                                .deps
+                                .iter()
+                                .enumerate()
+                                .map(|(j, dep)| {
+                                    let location = format!("#/pipe/{}/deps/{}", i, j);
+                                    match dep {
+                                        TaskOrRef::Task(task) => resolver
+                                            .resolve_task(task, &location)
+                                            .map(TaskOrRef::Task),
+                                        TaskOrRef::Ref(JsonRef { reference }) => reference
+                                            .strip_prefix(TASKS_PREFIX)
+                                            .map(unescape)
+                                            .and_then(|name| tasks.as_ref()?.get(&name))
+                                            .map(|task| TaskOrRef::Task(task.to_owned()))
+                                            .ok_or_else(|| VermanSchemaError::UnresolvedRef {
+                                                reference: reference.to_owned(),
+                                                location,
+                                            }),
+                                    }
+                                })
+                                .collect::<Result<Vec<TaskOrRef>, VermanSchemaError>>()?,
+                            ..stage.to_owned()
+                        })
+                    })
+                    .collect::<Result<Vec<Stage>, VermanSchemaError>>()?,
+            ),
+            None => None,
+        };
+        Ok(Pipeline {
+            pipe,
+            tasks,
+            ..self.to_owned()
+        })
+    }
+}
+
+struct Resolver {
+    /// `{"schemas": Pipeline.schemas}` so that `#/schemas/…` can be used as a JSON pointer
+    root: serde_json::Value,
+}
+
+impl Resolver {
+    fn resolve_task(&self, task: &Task, location: &str) -> Result<Task, VermanSchemaError> {
+        let resolve = |schema: &Option<JsonSchema>, field: &str| match schema {
+            Some(schema) => self
+                .resolve_schema(schema, &format!("{}/{}", location, field), &mut Vec::new())
+                .map(Some),
+            None => Ok(None),
+        };
+        Ok(Task {
+            input_schema: resolve(&task.input_schema, "input_schema")?,
+            output_schema: resolve(&task.output_schema, "output_schema")?,
+            ..task.to_owned()
+        })
+    }
+
+    /// `stack` holds the `$ref`s being expanded, to detect cycles
+    fn resolve_schema(
+        &self,
+        schema: &serde_json::Value,
+        location: &str,
+        stack: &mut Vec<String>,
+    ) -> Result<serde_json::Value, VermanSchemaError> {
+        match schema {
+            serde_json::Value::Object(map) => {
+                if let Some(serde_json::Value::String(reference)) = map.get("$ref") {
+                    if reference.starts_with(SCHEMAS_PREFIX) {
+                        if stack.contains(reference) {
+                            let mut cycle = stack.to_owned();
+                            cycle.push(reference.to_owned());
+                            return Err(VermanSchemaError::CyclicRef(cycle));
+                        }
+                        let target = self.root.pointer(&reference[1..]).ok_or_else(|| {
+                            VermanSchemaError::UnresolvedRef {
+                                reference: reference.to_owned(),
+                                location: location.to_owned(),
+                            }
+                        })?;
+                        /* per JSON-reference, members other than `$ref` are ignored */
+                        stack.push(reference.to_owned());
+                        let resolved = self.resolve_schema(target, reference, stack)?;
+                        stack.pop();
+                        return Ok(resolved);
+                    }
+                }
+                Ok(serde_json::Value::Object(
+                    map.iter()
+                        .map(|(k, v)| {
+                            let location = format!("{}/{}", location, escape(k));
+                            Ok((k.to_owned(), self.resolve_schema(v, &location, stack)?))
+                        })
+                        .collect::<Result<serde_json::Map<String, serde_json::Value>, VermanSchemaError>>()?,
+                ))
+            }
+            serde_json::Value::Array(arr) => Ok(serde_json::Value::Array(
+                arr.iter()
+                    .enumerate()
+                    .map(|(i, v)| self.resolve_schema(v, &format!("{}/{}", location, i), stack))
+                    .collect::<Result<Vec<serde_json::Value>, VermanSchemaError>>()?,
+            )),
+            other => Ok(other.to_owned()),
+        }
+    }
+}
+
+/// JSON pointer escaping, see https://datatracker.ietf.org/doc/html/rfc6901#section-3
+pub(crate) fn escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+#[cfg(test)]
+#[path = "jsonref_test.rs"]
+mod tests;
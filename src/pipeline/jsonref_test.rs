@@ -0,0 +1,111 @@
+use crate::commands::command::CommandKey;
+use crate::errors::VermanSchemaError;
+use crate::models::{Pipeline, TaskOrRef};
+
+const PIPELINE_WITH_REFS: &str = r###"{
+  "name": "refs",
+  "version": "0.0.1",
+  "description": "",
+  "url": "",
+  "engine_version": "0.1.0",
+  "pipe": [
+    {"name": "stage0", "sequential": true, "deps": [{"$ref": "#/tasks/greet"}]}
+  ],
+  "tasks": {
+    "greet": {
+      "commands": [{"cmd": "Echo", "content": "{\"message\": \"hi\"}"}],
+      "output_schema": {"$ref": "#/schemas/Message"}
+    }
+  },
+  "schemas": {
+    "Message": {
+      "type": "object",
+      "properties": {"message": {"$ref": "#/schemas/NonEmptyString"}},
+      "required": ["message"]
+    },
+    "NonEmptyString": {"type": "string", "minLength": 1}
+  }
+}"###;
+
+#[test]
+fn resolve_task_and_schema_refs_test() {
+    let pipeline: Pipeline = serde_json::from_str(PIPELINE_WITH_REFS).unwrap();
+    let resolved = pipeline.resolve_refs().unwrap();
+    let expected_schema = serde_json::json!({
+        "type": "object",
+        "properties": {"message": {"type": "string", "minLength": 1}},
+        "required": ["message"]
+    });
+    assert_eq!(
+        resolved.tasks.as_ref().unwrap()["greet"].output_schema,
+        Some(expected_schema.clone())
+    );
+    match &resolved.pipe.as_ref().unwrap()[0].deps[0] {
+        TaskOrRef::Task(task) => assert_eq!(task.output_schema, Some(expected_schema)),
+        TaskOrRef::Ref(r) => panic!("{:?} left unresolved", r),
+    }
+}
+
+#[tokio::test]
+async fn process_pipeline_with_refs_test() {
+    let pipeline: Pipeline = serde_json::from_str(PIPELINE_WITH_REFS).unwrap();
+    let env = pipeline.process().await.unwrap().env.unwrap();
+    assert_eq!(
+        env.get(CommandKey::PreviousContent.to_string().as_str())
+            .unwrap(),
+        &serde_json::Value::String(String::from("{\"message\": \"hi\"}"))
+    );
+}
+
+#[test]
+fn unresolved_ref_test() {
+    let mut pipeline: Pipeline = serde_json::from_str(PIPELINE_WITH_REFS).unwrap();
+    pipeline
+        .schemas
+        .as_mut()
+        .unwrap()
+        .remove("NonEmptyString");
+    match pipeline.resolve_refs() {
+        Err(VermanSchemaError::UnresolvedRef {
+            reference,
+            location,
+        }) => {
+            assert_eq!(reference, "#/schemas/NonEmptyString");
+            assert_eq!(location, "#/schemas/Message/properties/message");
+        }
+        other => panic!("expected `UnresolvedRef` got {:?}", other),
+    }
+
+    let mut pipeline: Pipeline = serde_json::from_str(PIPELINE_WITH_REFS).unwrap();
+    pipeline.tasks = None;
+    match pipeline.resolve_refs() {
+        Err(VermanSchemaError::UnresolvedRef {
+            reference,
+            location,
+        }) => {
+            assert_eq!(reference, "#/tasks/greet");
+            assert_eq!(location, "#/pipe/0/deps/0");
+        }
+        other => panic!("expected `UnresolvedRef` got {:?}", other),
+    }
+}
+
+#[test]
+fn cyclic_ref_test() {
+    let mut pipeline: Pipeline = serde_json::from_str(PIPELINE_WITH_REFS).unwrap();
+    pipeline.schemas.as_mut().unwrap().insert(
+        String::from("NonEmptyString"),
+        serde_json::json!({"items": {"$ref": "#/schemas/Message"}}),
+    );
+    match pipeline.resolve_refs() {
+        Err(VermanSchemaError::CyclicRef(cycle)) => assert_eq!(
+            cycle,
+            vec![
+                "#/schemas/Message",
+                "#/schemas/NonEmptyString",
+                "#/schemas/Message"
+            ]
+        ),
+        other => panic!("expected `CyclicRef` got {:?}", other),
+    }
+}
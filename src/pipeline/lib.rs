@@ -1 +1,3 @@
+pub mod jsonref;
 pub mod pipeline;
+pub mod validate;
@@ -1,7 +1,26 @@
 use crate::commands::shared::merge_env;
+use crate::constants::predefined_constants;
 use crate::errors::VermanSchemaError;
-use crate::models::{CommonContent, Pipeline, Task};
-use crate::task::task::process_tasks_serially;
+use crate::models::{CommonContent, JsonRef, Pipeline, Stage, Task, TaskOrRef};
+use crate::scheduler::scheduler::{process_tasks_as_dag, SchedulerOptions};
+use crate::task::task::{process_tasks_concurrently_with_env, process_tasks_serially_with_env};
+
+#[derive(derive_more::Display)]
+pub enum StageKey {
+    #[display("STAGE_CURRENT_NAME")]
+    CurrentName,
+
+    #[display("STAGE_PREVIOUS_NAME")]
+    PreviousName,
+}
+
+/// How `Pipeline::process_with` runs a `Pipeline`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProcessOptions {
+    /// Only run pure `Command`s, see `Command::is_pure`; the others report what they would do
+    /// as their content instead, see `Command::dry_run`
+    pub dry_run: bool,
+}
 
 impl Default for Pipeline {
     fn default() -> Self {
@@ -12,6 +31,7 @@ impl Default for Pipeline {
             url: String::new(),
             engine_version: String::from("0.1.0"),
             env: None,
+            inherit_env: None,
             pipe: None,
             tasks: None,
             schemas: None,
@@ -20,45 +40,200 @@ impl Default for Pipeline {
 }
 
 impl Pipeline {
+    /// When `pipe` is set its `Stage`s drive execution; otherwise `tasks` are run serially.
+    /// `$ref`s are resolved beforehand, see `Pipeline::resolve_refs`.
     pub async fn process(&self) -> Result<CommonContent, VermanSchemaError> {
+        self.process_with(&ProcessOptions::default()).await
+    }
+
+    /// Like `Pipeline::process`, e.g., as a dry run with `ProcessOptions.dry_run`
+    pub async fn process_with(
+        &self,
+        options: &ProcessOptions,
+    ) -> Result<CommonContent, VermanSchemaError> {
         let pretty_name = format!(
             "{}@{} from {}\n{}",
             self.name, self.version, self.url, self.description
         );
         log::info!("Started processing {}", pretty_name);
-        let common = match &self.tasks {
-            Some(tasks) => {
+        let pipeline = self.resolve_refs()?;
+        let common = match (&pipeline.pipe, &pipeline.tasks) {
+            (Some(stages), _) => pipeline.process_stages(stages, options).await?,
+            (None, Some(tasks)) => {
                 let tasks_with_merged_env = tasks
                     .iter()
-                    .map(|(name, task)| {
-                        (
-                            name.to_owned(),
-                            Task {
-                                commands: task.commands.to_owned(),
-                                input_schema: task.input_schema.to_owned(),
-                                output_schema: task.output_schema.to_owned(),
-                                env: {
-                                    let mut task_env = task
-                                        .env
-                                        .to_owned()
-                                        .unwrap_or_else(|| indexmap::IndexMap::new());
-                                    merge_env(&mut task_env, &self.env);
-                                    Some(task_env)
-                                },
-                            },
-                        )
-                    })
+                    .map(|(name, task)| (name.to_owned(), pipeline.task_with_merged_env(task)))
                     .collect();
-                process_tasks_serially(&self.name, &tasks_with_merged_env).await?
+                process_tasks_serially_with_env(
+                    &pipeline.name,
+                    &tasks_with_merged_env,
+                    pipeline.base_env(),
+                    options,
+                )
+                .await?
             }
-            None => {
-                log::warn!("No tasks found in pipeline");
+            (None, None) => {
+                log::warn!("No stages nor tasks found in pipeline");
                 CommonContent::default()
             }
         };
         log::info!("Finished processing {}", pretty_name);
         Ok(common)
     }
+
+    /// Run `tasks` concurrently, ordered by their dependencies; see `scheduler::process_tasks_as_dag`
+    pub async fn process_with_scheduler(
+        &self,
+        options: &SchedulerOptions,
+        process_options: &ProcessOptions,
+    ) -> Result<CommonContent, VermanSchemaError> {
+        let pipeline = self.resolve_refs()?;
+        match &pipeline.tasks {
+            Some(tasks) => {
+                let tasks_with_merged_env = tasks
+                    .iter()
+                    .map(|(name, task)| (name.to_owned(), pipeline.task_with_merged_env(task)))
+                    .collect();
+                process_tasks_as_dag(
+                    &pipeline.name,
+                    &tasks_with_merged_env,
+                    pipeline.base_env(),
+                    options,
+                    process_options,
+                )
+                .await
+            }
+            None => {
+                log::warn!("No tasks found in pipeline");
+                Ok(CommonContent::default())
+            }
+        }
+    }
+
+    async fn process_stages(
+        &self,
+        stages: &[Stage],
+        options: &ProcessOptions,
+    ) -> Result<CommonContent, VermanSchemaError> {
+        let mut shared_env_for_stages = self.base_env();
+        for stage in stages {
+            log::info!("Executing stage {:#?}", stage.name);
+            shared_env_for_stages.insert(
+                StageKey::CurrentName.to_string(),
+                stage.name.to_owned().into(),
+            );
+            let tasks = self.stage_tasks(stage)?;
+            let common = if stage.sequential {
+                process_tasks_serially_with_env(&self.name, &tasks, shared_env_for_stages, options)
+                    .await?
+            } else {
+                process_tasks_concurrently_with_env(
+                    &self.name,
+                    &tasks,
+                    shared_env_for_stages,
+                    options,
+                )
+                .await?
+            };
+            shared_env_for_stages = common.env.unwrap_or_default();
+            shared_env_for_stages.swap_remove(StageKey::CurrentName.to_string().as_str());
+            shared_env_for_stages.insert(
+                StageKey::PreviousName.to_string(),
+                stage.name.to_owned().into(),
+            );
+        }
+        Ok(CommonContent {
+            env: Some(shared_env_for_stages),
+            ..CommonContent::default()
+        })
+    }
+
+    /// Env each task starts with, before any `Command` output is merged in.
+    /// Tasks of `Stage`s are named `stage_name[idx]`.
+    pub fn task_envs(
+        &self,
+    ) -> Result<
+        indexmap::IndexMap<String, indexmap::IndexMap<String, serde_json::Value>>,
+        VermanSchemaError,
+    > {
+        let pipeline = self.resolve_refs()?;
+        let tasks = match (&pipeline.pipe, &pipeline.tasks) {
+            (Some(stages), _) => {
+                let mut tasks = indexmap::IndexMap::<String, Task>::new();
+                for stage in stages {
+                    tasks.extend(pipeline.stage_tasks(stage)?);
+                }
+                tasks
+            }
+            (None, Some(tasks)) => tasks
+                .iter()
+                .map(|(name, task)| (name.to_owned(), pipeline.task_with_merged_env(task)))
+                .collect(),
+            (None, None) => indexmap::IndexMap::new(),
+        };
+        Ok(tasks
+            .into_iter()
+            .map(|(name, task)| {
+                let mut env = pipeline.base_env();
+                merge_env(&mut env, &task.env);
+                (name, env)
+            })
+            .collect())
+    }
+
+    /// Env every task starts from, before `Pipeline.env` and `Task.env` are merged on top.
+    /// Follows "Resolving configuration" of README.md: inherited process env, then predefined
+    /// constants.
+    pub fn base_env(&self) -> indexmap::IndexMap<String, serde_json::Value> {
+        self.base_env_from(crate::system_env::process_vars())
+    }
+
+    /// `base_env` inheriting from `vars` rather than from the process env
+    pub(crate) fn base_env_from(
+        &self,
+        vars: impl Iterator<Item = (String, String)>,
+    ) -> indexmap::IndexMap<String, serde_json::Value> {
+        let mut env = match &self.inherit_env {
+            Some(inherit_env) => inherit_env.filter(vars),
+            None => indexmap::IndexMap::new(),
+        };
+        env.extend(predefined_constants());
+        env
+    }
+
+    /// `Stage.deps` named `stage_name[idx]`, with `Pipeline.env` merged in
+    fn stage_tasks(
+        &self,
+        stage: &Stage,
+    ) -> Result<indexmap::IndexMap<String, Task>, VermanSchemaError> {
+        stage
+            .deps
+            .iter()
+            .enumerate()
+            .map(|(i, dep)| match dep {
+                TaskOrRef::Task(task) => Ok((
+                    format!("{}[{}]", stage.name, i),
+                    self.task_with_merged_env(task),
+                )),
+                TaskOrRef::Ref(JsonRef { reference }) => Err(VermanSchemaError::UnresolvedRef {
+                    reference: reference.to_owned(),
+                    location: format!("{}[{}]", stage.name, i),
+                }),
+            })
+            .collect()
+    }
+
+    /// `Pipeline.env` takes priority over `Task.env`
+    pub(crate) fn task_with_merged_env(&self, task: &Task) -> Task {
+        Task {
+            env: {
+                let mut task_env = task.env.to_owned().unwrap_or_default();
+                merge_env(&mut task_env, &self.env);
+                Some(task_env)
+            },
+            ..task.to_owned()
+        }
+    }
 }
 
 #[cfg(test)]
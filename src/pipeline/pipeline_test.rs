@@ -1,6 +1,11 @@
 use crate::commands::command::{Command, CommandKey};
+use crate::constants::predefined_constants;
 use crate::errors::VermanSchemaError;
-use crate::models::{CommonContent, HttpArgs, HttpCommandArgs, Pipeline, Task};
+use crate::models::{
+    CommonContent, ExecArgs, ExecCommandArgs, Expectation, HttpArgs, HttpCommandArgs, InheritEnv,
+    Pipeline, Stage, Task,
+};
+use crate::pipeline::pipeline::{ProcessOptions, StageKey};
 use crate::task::task::TaskKey;
 use crate::test_models::{HttpBinPostResponse, Message, HTTPBIN_URL};
 
@@ -51,6 +56,7 @@ async fn empty_echo_task_empty_commands_pipeline_test() {
             input_schema: None,
             output_schema: None,
             env: None,
+            ..Task::default()
         }
         }),
         schemas: None,
@@ -77,11 +83,13 @@ async fn one_empty_echo_task_one_command_pipeline_test() {
             commands: vec![Command::Echo(CommonContent {
                     content: None,
                     env: None,
+                    ..CommonContent::default()
                 }),
             ],
             input_schema: None,
             output_schema: None,
             env: None,
+            ..Task::default()
         } }),
         schemas: None,
         ..Pipeline::default()
@@ -112,10 +120,12 @@ async fn one_echo_task_pipeline_test() {
                     env: Some(indexmap::indexmap! {
                         String::from("FOO") => serde_json::Value::String(String::from("bar"))
                     }),
+                    ..CommonContent::default()
                 })],
             input_schema: None,
             output_schema: None,
             env: None,
+            ..Task::default()
         }
         }),
         schemas: None,
@@ -162,12 +172,14 @@ async fn one_http_task_pipeline_test() {
                     CommonContent {
                         content: Some(serde_json::to_value(&message_input).unwrap()),
                         env: None,
+                        ..CommonContent::default()
                     },
                     Default::default(),
                 ))],
             input_schema: None,
             output_schema: None,
             env: None,
+            ..Task::default()
         }
         }),
         schemas: None,
@@ -222,6 +234,7 @@ async fn one_echo_one_http_command_in_one_task_pipeline_test() {
                         env: Some(indexmap::indexmap! {
                             String::from("ME") => serde_json::Value::String(String::from("Omega"))
                         }),
+                        ..CommonContent::default()
                     }),
                 Command::HttpClient(HttpCommandArgs::new(
                         HttpArgs {
@@ -237,6 +250,7 @@ async fn one_echo_one_http_command_in_one_task_pipeline_test() {
                             content: None,
                             /* defaults to "-", i.e., the result of the previous task */
                             env: None,
+                            ..CommonContent::default()
                         },
                         Default::default(),
                     )),
@@ -244,6 +258,7 @@ async fn one_echo_one_http_command_in_one_task_pipeline_test() {
             input_schema: None,
             output_schema: None,
             env: None,
+            ..Task::default()
         }
         }),
         schemas: None,
@@ -305,11 +320,13 @@ async fn one_set_env_one_echo_one_http_command_one_jaq_in_one_task_pipeline_test
                         content: None,
                         env: Some(indexmap::indexmap! {
                             String::from("ME") => serde_json::Value::String(String::from("Omega"))
-                        })
+                        }),
+                        ..CommonContent::default()
                     }),
                 Command::Echo(CommonContent {
                         content: Some(serde_json::to_value(&message_input).unwrap()),
                         env: None,
+                        ..CommonContent::default()
                     }),
                 Command::HttpClient(HttpCommandArgs::new(
                         HttpArgs {
@@ -325,17 +342,20 @@ async fn one_set_env_one_echo_one_http_command_one_jaq_in_one_task_pipeline_test
                             content: None,
                             /* defaults to "-", i.e., the result of the previous task */
                             env: None,
+                            ..CommonContent::default()
                         },
                         Default::default(),
                     )),
                     Command::Jaq(CommonContent {
                         content: Some(serde_json::Value::String(String::from(".json.message"))),
                         env: None,
+                        ..CommonContent::default()
                     })
             ],
             input_schema: None,
             output_schema: None,
             env: None,
+            ..Task::default()
         }
         }),
         schemas: None,
@@ -366,3 +386,342 @@ async fn one_set_env_one_echo_one_http_command_one_jaq_in_one_task_pipeline_test
         String::from("\"greetings to Omega\"")
     );
 }
+
+#[tokio::test]
+async fn sequential_then_concurrent_echo_stages_pipeline_test() {
+    let pipeline7: Pipeline = Pipeline {
+        name: String::from(env!("CARGO_PKG_NAME")),
+        version: String::from(env!("CARGO_PKG_VERSION")),
+        description: String::from(env!("CARGO_PKG_DESCRIPTION")),
+        url: String::from(env!("CARGO_PKG_REPOSITORY")),
+        env: None,
+        pipe: Some(vec![
+            Stage {
+                name: String::from("greet"),
+                deps: vec![
+                    Task {
+                        commands: vec![Command::Echo(CommonContent {
+                            content: Some(serde_json::Value::String(String::from("hello ${WHO}"))),
+                            env: Some(indexmap::indexmap! {
+                                String::from("WHO") => serde_json::Value::String(String::from("world"))
+                            }),
+                            ..CommonContent::default()
+                        })],
+                        ..Task::default()
+                    }
+                    .into(),
+                    Task {
+                        commands: vec![Command::Echo(CommonContent {
+                            content: Some(serde_json::Value::String(String::from(
+                                "${CMD_PREVIOUS_CONTENT}!",
+                            ))),
+                            env: None,
+                            ..CommonContent::default()
+                        })],
+                        ..Task::default()
+                    }
+                    .into(),
+                ],
+                sequential: true,
+            },
+            Stage {
+                name: String::from("shout"),
+                deps: vec![
+                    Task {
+                        commands: vec![Command::Echo(CommonContent {
+                            content: Some(serde_json::Value::String(String::from("a"))),
+                            env: None,
+                            ..CommonContent::default()
+                        })],
+                        ..Task::default()
+                    }
+                    .into(),
+                    Task {
+                        commands: vec![Command::Echo(CommonContent {
+                            content: Some(serde_json::Value::String(String::from(
+                                "${CMD_PREVIOUS_CONTENT} b",
+                            ))),
+                            env: None,
+                            ..CommonContent::default()
+                        })],
+                        ..Task::default()
+                    }
+                    .into(),
+                ],
+                sequential: false,
+            },
+        ]),
+        tasks: None,
+        schemas: None,
+        ..Pipeline::default()
+    };
+    let common = pipeline7.process().await.unwrap();
+    let env = common.env.unwrap();
+    assert_eq!(
+        env.get(StageKey::PreviousName.to_string().as_str()).unwrap(),
+        &serde_json::Value::String(String::from("shout"))
+    );
+    assert_eq!(
+        env.get(TaskKey::PreviousName.to_string().as_str()).unwrap(),
+        &serde_json::Value::String(String::from("shout[1]"))
+    );
+    assert_eq!(
+        env.get(CommandKey::PreviousContent.to_string().as_str())
+            .unwrap(),
+        &serde_json::Value::String(String::from("hello world! b"))
+    );
+    assert!(!env.contains_key(StageKey::CurrentName.to_string().as_str()));
+    assert!(!env.contains_key(TaskKey::CurrentName.to_string().as_str()));
+}
+
+#[test]
+fn task_envs_test() {
+    let pipeline8: Pipeline = Pipeline {
+        env: Some(indexmap::indexmap! {
+            String::from("FOO") => serde_json::Value::String(String::from("pipeline"))
+        }),
+        pipe: Some(vec![Stage {
+            name: String::from("stage0"),
+            deps: vec![Task {
+                env: Some(indexmap::indexmap! {
+                    String::from("FOO") => serde_json::Value::String(String::from("task")),
+                    String::from("BAR") => serde_json::Value::String(String::from("task"))
+                }),
+                ..Task::default()
+            }
+            .into()],
+            sequential: true,
+        }]),
+        ..Pipeline::default()
+    };
+    let mut expected_env = predefined_constants();
+    expected_env.extend(indexmap::indexmap! {
+        String::from("FOO") => serde_json::Value::String(String::from("pipeline")),
+        String::from("BAR") => serde_json::Value::String(String::from("task"))
+    });
+    assert_eq!(
+        pipeline8.task_envs().unwrap(),
+        indexmap::indexmap! { String::from("stage0[0]") => expected_env }
+    );
+}
+
+#[tokio::test]
+async fn predefined_constants_pipeline_test() {
+    let pipeline9: Pipeline = Pipeline {
+        env: Some(indexmap::indexmap! {
+            String::from("ARCH") => serde_json::Value::String(String::from("overridden"))
+        }),
+        tasks: Some(indexmap::indexmap! {
+            String::from("task0") => Task {
+                commands: vec![Command::Echo(CommonContent {
+                    content: Some(serde_json::Value::String(String::from("${OS} ${FAMILY} ${ARCH}"))),
+                    env: None,
+                    ..CommonContent::default()
+                })],
+                ..Task::default()
+            }
+        }),
+        ..Pipeline::default()
+    };
+    let env = pipeline9.process().await.unwrap().env.unwrap();
+    assert_eq!(
+        env.get(CommandKey::PreviousContent.to_string().as_str())
+            .unwrap(),
+        &serde_json::Value::String(format!(
+            "{} {} overridden",
+            std::env::consts::OS,
+            std::env::consts::FAMILY
+        ))
+    );
+    assert!(env.get("BUILD_TIME").unwrap().as_u64().unwrap() > 0);
+    assert_ne!(crate::verman_schema::Os::current(), crate::verman_schema::Os::Unspecified);
+}
+
+#[test]
+fn inherit_env_filter_test() {
+    let vars = || {
+        vec![
+            (String::from("VERMAN_A"), String::from("a")),
+            (String::from("VERMAN_SECRET"), String::from("s")),
+            (String::from("HOME"), String::from("/home/me")),
+            (String::from("PATH"), String::from("/bin")),
+        ]
+        .into_iter()
+    };
+    assert_eq!(InheritEnv::default().filter(vars()).len(), 4);
+    assert_eq!(
+        InheritEnv {
+            allow: Some(vec![String::from("HOME")]),
+            prefixes: Some(vec![String::from("VERMAN_")]),
+            deny: Some(vec![String::from("VERMAN_SECRET")]),
+        }
+        .filter(vars()),
+        indexmap::indexmap! {
+            String::from("HOME") => serde_json::Value::String(String::from("/home/me")),
+            String::from("VERMAN_A") => serde_json::Value::String(String::from("a")),
+        }
+    );
+}
+
+#[test]
+fn inherit_env_base_env_test() {
+    let vars = || {
+        vec![
+            (
+                String::from("VERMAN_INHERIT_ENV_TEST"),
+                String::from("inherited"),
+            ),
+            (String::from("OS"), String::from("from the process env")),
+        ]
+        .into_iter()
+    };
+    let mut pipeline10: Pipeline = Pipeline::default();
+    assert!(!pipeline10
+        .base_env_from(vars())
+        .contains_key("VERMAN_INHERIT_ENV_TEST"));
+    pipeline10.inherit_env = Some(InheritEnv {
+        allow: Some(vec![String::from("OS")]),
+        prefixes: Some(vec![String::from("VERMAN_INHERIT_")]),
+        deny: None,
+    });
+    let env = pipeline10.base_env_from(vars());
+    assert_eq!(
+        env.get("VERMAN_INHERIT_ENV_TEST").unwrap(),
+        &serde_json::Value::String(String::from("inherited"))
+    );
+    /* predefined constants take priority over the process env */
+    assert_eq!(
+        env.get("OS").unwrap(),
+        &serde_json::Value::String(String::from(std::env::consts::OS))
+    );
+}
+
+#[tokio::test]
+async fn dry_run_pipeline_test() {
+    /* only exists if the `Exec` actually ran */
+    let marker = std::env::temp_dir()
+        .join(format!("verman_dry_run_{}", std::process::id()))
+        .display()
+        .to_string();
+    let _ = std::fs::remove_file(&marker);
+    let exec = Command::Exec(ExecCommandArgs {
+        args: ExecArgs {
+            program: String::from("/bin/sh"),
+            args: vec![String::from("-c"), String::from("touch \"$MARKER\"")],
+            cwd: None,
+        },
+        ..ExecCommandArgs::default()
+    });
+    let pipeline11: Pipeline = Pipeline {
+        name: String::from("p"),
+        env: Some(indexmap::indexmap! {
+            String::from("TARGET") => serde_json::Value::String(String::from("/tmp/nothing")),
+            String::from("MARKER") => serde_json::Value::String(marker.to_owned()),
+        }),
+        tasks: Some(indexmap::indexmap! {
+            String::from("task0") => Task {
+                commands: vec![
+                    Command::Echo(CommonContent {
+                        content: Some(serde_json::Value::String(String::from("${TARGET}"))),
+                        env: None,
+                        ..CommonContent::default()
+                    }),
+                    Command::HttpClient(HttpCommandArgs::new(
+                        HttpArgs {
+                            /* nothing listens there, so a request would fail */
+                            url: http::uri::Uri::from_static("http://127.0.0.1:1/post"),
+                            method: http::method::Method::POST,
+                            headers: Some(vec![indexmap::indexmap! {
+                                String::from("X-Target") => serde_json_extensions::ValueNoObjOrArr::String(String::from("${TARGET}"))
+                            }]),
+                        },
+                        CommonContent::default(),
+                        Expectation::default(),
+                    )),
+                    Command::Jaq(CommonContent {
+                        content: Some(serde_json::Value::String(String::from(".body | ascii_upcase"))),
+                        env: None,
+                        ..CommonContent::default()
+                    }),
+                    exec,
+                ],
+                /* not checked against the reports of a dry run */
+                output_schema: Some(serde_json::json!({"type": "string"})),
+                ..Task::default()
+            }
+        }),
+        ..Pipeline::default()
+    };
+    let env = pipeline11
+        .process_with(&ProcessOptions { dry_run: true })
+        .await
+        .unwrap()
+        .env
+        .unwrap();
+    assert_eq!(
+        env.get("p__task0[1]_CMD_CONTENT").unwrap(),
+        &serde_json::json!({
+            "cmd": "HttpClient",
+            "method": "POST",
+            "url": "http://127.0.0.1:1/post",
+            "headers": {"x-target": "/tmp/nothing"},
+            "body": "/tmp/nothing"
+        })
+    );
+    assert_eq!(
+        env.get("p__task0[2]_CMD_CONTENT").unwrap(),
+        &serde_json::json!({"cmd": "Jaq", "filter": ".body | ascii_upcase"})
+    );
+    assert_eq!(
+        env.get(CommandKey::PreviousContent.to_string().as_str())
+            .unwrap(),
+        &serde_json::json!({
+            "cmd": "Exec",
+            "program": "/bin/sh",
+            "args": ["-c", "touch \"$MARKER\""],
+            "cwd": null,
+            "content": null
+        })
+    );
+    assert!(!std::path::Path::new(&marker).exists());
+
+    /* filters still have to compile */
+    let pipeline12 = Pipeline {
+        tasks: Some(indexmap::indexmap! {
+            String::from("task0") => Task {
+                commands: vec![Command::Jaq(CommonContent {
+                    content: Some(serde_json::Value::String(String::from(".[0] |"))),
+                    env: None,
+                    ..CommonContent::default()
+                })],
+                ..Task::default()
+            }
+        }),
+        ..Pipeline::default()
+    };
+    assert!(pipeline12
+        .process_with(&ProcessOptions { dry_run: true })
+        .await
+        .is_err());
+
+    /* as in a real run, unknown interpreters err */
+    let pipeline13 = Pipeline {
+        tasks: Some(indexmap::indexmap! {
+            String::from("task0") => Task {
+                commands: vec![Command::Script(CommonContent {
+                    content: Some(serde_json::Value::String(String::from("#!/bin/sh\ntouch \"$MARKER\""))),
+                    env: None,
+                    ..CommonContent::default()
+                })],
+                ..Task::default()
+            }
+        }),
+        ..Pipeline::default()
+    };
+    assert!(matches!(
+        pipeline13
+            .process_with(&ProcessOptions { dry_run: true })
+            .await,
+        Err(VermanSchemaError::NotInstalled(_))
+    ));
+}
@@ -0,0 +1,437 @@
+use std::str::FromStr;
+
+use crate::commands::command::{Command, CommandKey};
+use crate::commands::exec::ExecKey;
+use crate::commands::jaq::jaq_utils::vars_filter_from_code;
+use crate::commands::script::{parse_shebang, Shebang};
+use crate::commands::shared::make_subst_map;
+use crate::errors::{Diagnostic, VermanSchemaError};
+use crate::models::{CommonContent, HttpArgs, Pipeline, Task, TaskOrRef};
+use crate::pipeline::jsonref::escape;
+use crate::pipeline::pipeline::StageKey;
+use crate::task::task::TaskKey;
+
+impl Pipeline {
+    /// Check the whole `Pipeline` without running anything, see `Pipeline::diagnostics`.
+    /// Fails with every `Diagnostic` at once.
+    pub fn validate(&self) -> Result<(), VermanSchemaError> {
+        let diagnostics = self.diagnostics();
+        if diagnostics.is_empty() {
+            Ok(())
+        } else {
+            Err(VermanSchemaError::PipelineValidation {
+                pipeline: self.name.to_owned(),
+                diagnostics,
+            })
+        }
+    }
+
+    /// Walk the `Pipeline` in the order `Pipeline::process` would, following the env along:
+    /// - every `$ref` resolves;
+    /// - every `${VAR}` (without a default) is set by the time its `Command` runs;
+    /// - every jaq filter compiles;
+    /// - HTTP URLs are absolute `http`/`https` ones and headers are valid.
+    ///
+    /// Variables only known at run time (e.g., `CMD_PREVIOUS_CONTENT`) count as set, and once a
+    /// `Command` that may set arbitrary variables ran (e.g., `Command::Lua`), unset ones no longer
+    /// get reported.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        let pipeline = match self.resolve_refs() {
+            Ok(pipeline) => pipeline,
+            Err(VermanSchemaError::UnresolvedRef {
+                reference,
+                location,
+            }) => {
+                return vec![Diagnostic {
+                    location,
+                    message: format!("unresolved `$ref` {:?}", reference),
+                }]
+            }
+            Err(error) => {
+                return vec![Diagnostic {
+                    location: String::from("#"),
+                    message: error.to_string(),
+                }]
+            }
+        };
+        let mut validator = Validator {
+            pipeline_name: &pipeline.name,
+            diagnostics: Vec::new(),
+        };
+        let mut env = StaticEnv::from(pipeline.base_env());
+        match (&pipeline.pipe, &pipeline.tasks) {
+            (Some(stages), _) => {
+                for (i, stage) in stages.iter().enumerate() {
+                    env.insert(
+                        StageKey::CurrentName.to_string(),
+                        Some(stage.name.to_owned().into()),
+                    );
+                    let env_before_stage = env.clone();
+                    for (j, dep) in stage.deps.iter().enumerate() {
+                        if let TaskOrRef::Task(task) = dep {
+                            let task_name = format!("{}[{}]", stage.name, j);
+                            let location = format!("#/pipe/{}/deps/{}", i, j);
+                            let task = pipeline.task_with_merged_env(task);
+                            if stage.sequential {
+                                validator.task(&task_name, &location, &task, j, &mut env);
+                            } else {
+                                /* concurrent tasks only see the env from before the stage */
+                                let mut task_env = env_before_stage.clone();
+                                validator.task(&task_name, &location, &task, j, &mut task_env);
+                                env.extend(task_env);
+                            }
+                        }
+                    }
+                    env.swap_remove(StageKey::CurrentName.to_string().as_str());
+                    env.insert(
+                        StageKey::PreviousName.to_string(),
+                        Some(stage.name.to_owned().into()),
+                    );
+                }
+            }
+            (None, Some(tasks)) => {
+                for (i, (task_name, task)) in tasks.iter().enumerate() {
+                    let location = format!("#/tasks/{}", escape(task_name));
+                    let task = pipeline.task_with_merged_env(task);
+                    validator.task(task_name, &location, &task, i, &mut env);
+                }
+            }
+            (None, None) => {}
+        }
+        validator.diagnostics
+    }
+}
+
+/// Variables set at some point of a `Pipeline`, with their value when known before running
+#[derive(Clone, Debug, Default)]
+struct StaticEnv {
+    vars: indexmap::IndexMap<String, Option<serde_json::Value>>,
+    /// Set once a `Command` that may add arbitrary variables ran
+    open: bool,
+}
+
+impl From<indexmap::IndexMap<String, serde_json::Value>> for StaticEnv {
+    fn from(env: indexmap::IndexMap<String, serde_json::Value>) -> Self {
+        Self {
+            vars: env.into_iter().map(|(k, v)| (k, Some(v))).collect(),
+            open: false,
+        }
+    }
+}
+
+impl StaticEnv {
+    fn insert(&mut self, key: String, value: Option<serde_json::Value>) {
+        self.vars.insert(key, value);
+    }
+
+    fn swap_remove(&mut self, key: &str) {
+        self.vars.swap_remove(key);
+    }
+
+    /// Same as `commands::shared::merge_env`
+    fn merge(&mut self, superior: &Option<indexmap::IndexMap<String, serde_json::Value>>) {
+        if let Some(env) = superior {
+            self.vars
+                .extend(env.iter().map(|(k, v)| (k.to_owned(), Some(v.to_owned()))));
+        }
+    }
+
+    fn extend(&mut self, other: StaticEnv) {
+        self.vars.extend(other.vars);
+        self.open |= other.open;
+    }
+
+    fn is_set(&self, name: &str) -> bool {
+        self.open || self.vars.contains_key(name)
+    }
+
+    /// `s` interpolated, when the value of every variable it refers to is known
+    fn substitute(&self, s: &str) -> Option<String> {
+        let known: indexmap::IndexMap<String, serde_json::Value> = self
+            .vars
+            .iter()
+            .filter_map(|(k, v)| Some((k.to_owned(), v.to_owned()?)))
+            .collect();
+        if references(s)
+            .iter()
+            .any(|(name, has_default)| !known.contains_key(name) && !has_default)
+        {
+            return None;
+        }
+        subst::substitute(s, &make_subst_map(&known)).ok()
+    }
+}
+
+/// Names of the `${VAR}` and `${VAR:default}` in `s`, each with whether it has a default
+fn references(s: &str) -> Vec<(String, bool)> {
+    let mut refs = Vec::new();
+    let mut chars = s.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '$' if s[i + 1..].starts_with('{') => {
+                if let Some(end) = s[i + 2..].find('}') {
+                    let inner = &s[i + 2..i + 2 + end];
+                    refs.push(match inner.split_once(':') {
+                        Some((name, _)) => (name.to_owned(), true),
+                        None => (inner.to_owned(), false),
+                    });
+                    while chars.next_if(|(j, _)| *j <= i + 2 + end).is_some() {}
+                }
+            }
+            _ => {}
+        }
+    }
+    refs
+}
+
+struct Validator<'a> {
+    pipeline_name: &'a str,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Validator<'_> {
+    fn report(&mut self, location: String, message: String) {
+        self.diagnostics.push(Diagnostic { location, message });
+    }
+
+    /// Mirrors `task::task::prepare_task`, `Task::process` then `task::task::merge_task_result`
+    fn task(
+        &mut self,
+        task_name: &str,
+        location: &str,
+        task: &Task,
+        idx: usize,
+        env: &mut StaticEnv,
+    ) {
+        env.insert(
+            TaskKey::CurrentName.to_string(),
+            Some(task_name.to_owned().into()),
+        );
+        env.merge(&task.env);
+        for (j, command) in task.commands.iter().enumerate() {
+            self.command(command, &format!("{}/commands/{}", location, j), env);
+            if task.command_policy(command).continue_on_error {
+                env.insert(CommandKey::LastError.to_string(), None);
+            }
+            for key in [
+                CommandKey::PreviousContent.to_string(),
+                format!(
+                    "{}__{}[{}]_CMD_CONTENT",
+                    self.pipeline_name,
+                    task_name,
+                    idx + j
+                ),
+                format!("{}__{}_CMD_CONTENT", self.pipeline_name, task_name),
+            ] {
+                env.insert(key, None);
+            }
+        }
+        env.swap_remove(TaskKey::CurrentName.to_string().as_str());
+        env.swap_remove(CommandKey::CurrentContent.to_string().as_str());
+        env.insert(
+            TaskKey::PreviousName.to_string(),
+            Some(task_name.to_owned().into()),
+        );
+    }
+
+    fn command(&mut self, command: &Command, location: &str, env: &mut StaticEnv) {
+        match command {
+            Command::Echo(arg) | Command::Env(arg) => {
+                env.merge(&arg.env);
+                if let Some(serde_json::Value::String(s)) = &arg.content {
+                    self.references(s, &format!("{}/content", location), env);
+                }
+            }
+            Command::Exec(arg) => {
+                env.merge(&arg.common_content.env);
+                env.insert(ExecKey::Stderr.to_string(), None);
+                env.insert(ExecKey::ExitCode.to_string(), None);
+            }
+            Command::HttpClient(arg) => {
+                env.merge(&arg.common_content.env);
+                self.http(&arg.args, &arg.common_content, location, env);
+                env.insert(CommandKey::PreviousType.to_string(), None);
+            }
+            Command::Interpolate(arg) => {
+                env.merge(&arg.env);
+                self.content_references(&arg.content, &format!("{}/content", location), env);
+            }
+            Command::Jaq(arg) => {
+                env.merge(&arg.env);
+                self.jaq(&arg.content, &format!("{}/content", location), env);
+            }
+            Command::Script(arg) => {
+                env.merge(&arg.env);
+                let content_location = format!("{}/content", location);
+                match &arg.content {
+                    Some(serde_json::Value::String(s)) => match parse_shebang(s) {
+                        Ok((Shebang::Echo, body)) => self.references(body, &content_location, env),
+                        Ok((Shebang::Jq, body)) => self.jaq(
+                            &Some(serde_json::Value::String(body.to_owned())),
+                            &content_location,
+                            env,
+                        ),
+                        Ok(_) => env.open = true,
+                        Err(error) => self.report(content_location, error.to_string()),
+                    },
+                    _ => self.report(content_location, String::from("missing script")),
+                }
+            }
+            Command::SetEnv(arg) => env.merge(&arg.env),
+            #[cfg(feature = "js")]
+            Command::Js(arg) => {
+                env.merge(&arg.env);
+                env.open = true;
+            }
+            #[cfg(feature = "lua")]
+            Command::Lua(arg) => {
+                env.merge(&arg.env);
+                env.open = true;
+            }
+            #[cfg(feature = "python")]
+            Command::Python(arg) => {
+                env.merge(&arg.env);
+                env.open = true;
+            }
+            #[cfg(feature = "wasm")]
+            Command::Wasm(arg) => {
+                env.merge(&arg.common_content.env);
+                env.open = true;
+            }
+        }
+    }
+
+    fn references(&mut self, s: &str, location: &str, env: &StaticEnv) {
+        for (name, has_default) in references(s) {
+            if !has_default && !env.is_set(&name) {
+                self.report(
+                    location.to_owned(),
+                    format!("`${{{}}}` is not set at this point", name),
+                );
+            }
+        }
+    }
+
+    /// Like `references`; non-string `content` is interpolated as JSON
+    fn content_references(
+        &mut self,
+        content: &Option<serde_json::Value>,
+        location: &str,
+        env: &StaticEnv,
+    ) {
+        match content {
+            None | Some(serde_json::Value::Null) => {}
+            Some(serde_json::Value::String(s)) => self.references(s, location, env),
+            Some(val) => self.references(&val.to_string(), location, env),
+        }
+    }
+
+    fn jaq(&mut self, content: &Option<serde_json::Value>, location: &str, env: &StaticEnv) {
+        let filter = match content {
+            None | Some(serde_json::Value::Null) => {
+                return self.report(location.to_owned(), String::from("missing filter"))
+            }
+            Some(serde_json::Value::String(s)) if s.as_str() == "-" => return,
+            Some(serde_json::Value::String(s)) => s,
+            Some(_) => {
+                return self.report(location.to_owned(), String::from("filter must be a string"))
+            }
+        };
+        if !env.is_set(CommandKey::PreviousContent.to_string().as_str()) {
+            self.report(
+                location.to_owned(),
+                format!(
+                    "no input, `{}` is not set at this point",
+                    CommandKey::PreviousContent
+                ),
+            );
+        }
+        self.references(filter, location, env);
+        if let Some(filter) = env.substitute(filter) {
+            if let Err(error) = vars_filter_from_code(&filter) {
+                self.report(
+                    location.to_owned(),
+                    format!("filter does not compile: {}", error),
+                );
+            }
+        }
+    }
+
+    /// Same interpolation as `commands::http_client::http`
+    fn http(
+        &mut self,
+        args: &HttpArgs,
+        common_content: &CommonContent,
+        location: &str,
+        env: &StaticEnv,
+    ) {
+        let url_location = format!("{}/args/url", location);
+        let url = args.url.to_string();
+        self.references(&url, &url_location, env);
+        if let Some(url) = env.substitute(&url) {
+            match http::uri::Uri::from_str(&url) {
+                Ok(uri) => {
+                    if !matches!(uri.scheme_str(), Some("http") | Some("https")) {
+                        self.report(
+                            url_location,
+                            format!("{:?} is not an `http` or `https` URL", url),
+                        );
+                    } else if uri.authority().is_none() {
+                        self.report(url_location, format!("{:?} has no host", url));
+                    }
+                }
+                Err(error) => self.report(url_location, error.to_string()),
+            }
+        }
+
+        let method_location = format!("{}/args/method", location);
+        let method = args.method.to_string();
+        self.references(&method, &method_location, env);
+        if let Some(method) = env.substitute(&method) {
+            if let Err(error) = http::method::Method::from_str(&method) {
+                self.report(method_location, error.to_string());
+            }
+        }
+
+        for (i, headers) in args.headers.iter().flatten().enumerate() {
+            for (name, value) in headers {
+                let header_location = format!("{}/args/headers/{}/{}", location, i, escape(name));
+                if let Err(error) = http::header::HeaderName::from_str(name) {
+                    self.report(header_location.to_owned(), error.to_string());
+                }
+                match value {
+                    serde_json_extensions::ValueNoObjOrArr::String(s) => {
+                        self.references(s, &header_location, env);
+                        if let Some(s) = env.substitute(s) {
+                            if let Err(error) = http::header::HeaderValue::from_str(&s) {
+                                self.report(header_location, error.to_string());
+                            }
+                        }
+                    }
+                    serde_json_extensions::ValueNoObjOrArr::Number(
+                        serde_json_extensions::number::Number {
+                            n: serde_json_extensions::number::N::Float(_),
+                        },
+                    ) => self.report(
+                        header_location,
+                        String::from("floating point header values are not supported"),
+                    ),
+                    _ => {}
+                }
+            }
+        }
+
+        self.content_references(
+            &common_content.content,
+            &format!("{}/common_content/content", location),
+            env,
+        );
+    }
+}
+
+#[cfg(test)]
+#[path = "validate_test.rs"]
+mod tests;
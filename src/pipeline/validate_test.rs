@@ -0,0 +1,172 @@
+use crate::errors::{Diagnostic, VermanSchemaError};
+use crate::models::Pipeline;
+
+fn pipeline(rest: serde_json::Value) -> Pipeline {
+    let mut pipeline = serde_json::json!({
+        "name": "p",
+        "version": "0.0.1",
+        "description": "",
+        "url": "",
+        "engine_version": "0.1.0"
+    });
+    pipeline
+        .as_object_mut()
+        .unwrap()
+        .extend(rest.as_object().unwrap().to_owned());
+    serde_json::from_value(pipeline).unwrap()
+}
+
+fn locations(pipeline: &Pipeline) -> Vec<String> {
+    pipeline
+        .diagnostics()
+        .into_iter()
+        .map(|Diagnostic { location, .. }| location)
+        .collect()
+}
+
+#[test]
+fn validate_valid_pipeline_test() {
+    let pipeline = pipeline(serde_json::json!({
+        "env": {"GREETING": "hello", "HOST": "example.com"},
+        "tasks": {
+            "task0": {
+                "commands": [
+                    {"cmd": "Echo", "content": "${GREETING} ${NAME}", "env": {"NAME": "world"}},
+                    {"cmd": "Jaq", "content": ".[0] | ascii_upcase"},
+                    {
+                        "cmd": "HttpClient",
+                        "args": {
+                            "url": "https://example.com/post",
+                            "method": "POST",
+                            "headers": [{"Content-Type": "application/json", "X-Host": "${HOST}"}]
+                        },
+                        "common_content": {"content": "${CMD_PREVIOUS_CONTENT}"},
+                        "expectation": {"status_code": 200, "exit_code": 0}
+                    }
+                ]
+            },
+            "task1": {
+                "commands": [
+                    {"cmd": "Echo", "content": "${p__task0_CMD_CONTENT} ${MISSING:fallback}"}
+                ]
+            }
+        }
+    }));
+    assert_eq!(pipeline.diagnostics(), vec![]);
+    assert!(pipeline.validate().is_ok());
+}
+
+#[test]
+fn validate_reports_every_problem_test() {
+    let pipeline = pipeline(serde_json::json!({
+        "tasks": {
+            "task0": {
+                "commands": [
+                    {"cmd": "Jaq", "content": "."},
+                    {"cmd": "Echo", "content": "${LATER} ${p__task1_CMD_CONTENT}"},
+                    {"cmd": "SetEnv", "env": {"LATER": "now"}},
+                    {"cmd": "Jaq"},
+                    {"cmd": "Jaq", "content": ".[0] |"},
+                    {
+                        "cmd": "HttpClient",
+                        "args": {
+                            "url": "/relative",
+                            "method": "GET",
+                            "headers": [{"Bad Header": "x", "X-Ok": "${UNSET}"}]
+                        },
+                        "common_content": {},
+                        "expectation": {"status_code": 200, "exit_code": 0}
+                    }
+                ]
+            },
+            "task1": {"commands": [{"cmd": "Echo", "content": "${LATER}"}]}
+        }
+    }));
+    assert_eq!(
+        locations(&pipeline),
+        vec![
+            "#/tasks/task0/commands/0/content",
+            "#/tasks/task0/commands/1/content",
+            "#/tasks/task0/commands/1/content",
+            "#/tasks/task0/commands/3/content",
+            "#/tasks/task0/commands/4/content",
+            "#/tasks/task0/commands/5/args/url",
+            "#/tasks/task0/commands/5/args/headers/0/Bad Header",
+            "#/tasks/task0/commands/5/args/headers/0/X-Ok",
+        ]
+    );
+    match pipeline.validate() {
+        Err(VermanSchemaError::PipelineValidation {
+            pipeline,
+            diagnostics,
+        }) => {
+            assert_eq!(pipeline, "p");
+            assert_eq!(diagnostics.len(), 8);
+            assert_eq!(
+                diagnostics[1].message,
+                "`${LATER}` is not set at this point"
+            );
+        }
+        other => panic!("expected `PipelineValidation` got {:?}", other),
+    }
+}
+
+#[test]
+fn validate_stages_test() {
+    let pipeline = pipeline(serde_json::json!({
+        "pipe": [
+            {
+                "name": "first",
+                "sequential": false,
+                "deps": [
+                    {"commands": [{"cmd": "SetEnv", "env": {"A": "a"}}]},
+                    {"commands": [{"cmd": "Echo", "content": "${A} ${STAGE_CURRENT_NAME}"}]}
+                ]
+            },
+            {
+                "name": "second",
+                "sequential": true,
+                "deps": [
+                    {"$ref": "#/tasks/uses_a"},
+                    {"$ref": "#/tasks/missing"}
+                ]
+            }
+        ],
+        "tasks": {
+            "uses_a": {"commands": [{"cmd": "Echo", "content": "${A} ${STAGE_PREVIOUS_NAME}"}]}
+        }
+    }));
+    assert_eq!(locations(&pipeline), vec!["#/pipe/1/deps/1"]);
+
+    let pipeline = Pipeline {
+        pipe: pipeline.pipe.to_owned().map(|mut stages| {
+            stages[1].deps.pop();
+            stages
+        }),
+        ..pipeline
+    };
+    /* `A` is set by a concurrent sibling, so only later stages can rely on it */
+    assert_eq!(
+        locations(&pipeline),
+        vec!["#/pipe/0/deps/1/commands/0/content"]
+    );
+}
+
+#[test]
+fn validate_after_script_test() {
+    let pipeline = pipeline(serde_json::json!({
+        "tasks": {
+            "task0": {
+                "commands": [
+                    {"cmd": "Script", "content": "#!/echo\n${BEFORE}"},
+                    {"cmd": "Script", "content": "#!/python\nenv['AFTER'] = 1"},
+                    {"cmd": "Echo", "content": "${AFTER}"}
+                ]
+            }
+        }
+    }));
+    assert_eq!(
+        locations(&pipeline),
+        vec!["#/tasks/task0/commands/0/content"]
+    );
+}
@@ -0,0 +1,196 @@
+use futures::StreamExt;
+
+use crate::commands::shared::merge_env;
+use crate::errors::VermanSchemaError;
+use crate::models::{CommonContent, Task};
+use crate::pipeline::pipeline::ProcessOptions;
+use crate::task::task::{merge_task_result, prepare_task};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchedulerOptions {
+    /// Maximum number of `Task`s running at the same time
+    pub parallelism: usize,
+}
+
+impl Default for SchedulerOptions {
+    fn default() -> Self {
+        Self {
+            parallelism: std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(4),
+        }
+    }
+}
+
+/// Map of task name to the names of the tasks it depends on, in declaration order.
+/// Dependencies come from `Task.depends_on` and from references to the
+/// `${pipeline__task_CMD_CONTENT}` / `${pipeline__task[idx]_CMD_CONTENT}` keys written by
+/// `Command::cache`.
+pub fn dependency_graph(
+    pipeline_name: &String,
+    tasks: &indexmap::IndexMap<String, Task>,
+) -> Result<indexmap::IndexMap<String, indexmap::IndexSet<String>>, VermanSchemaError> {
+    let mut graph =
+        indexmap::IndexMap::<String, indexmap::IndexSet<String>>::with_capacity(tasks.len());
+    for (task_name, task) in tasks.iter() {
+        let mut deps = indexmap::IndexSet::<String>::new();
+        for dep in task.depends_on.iter().flatten() {
+            if !tasks.contains_key(dep) {
+                return Err(VermanSchemaError::Task(format!(
+                    "task {:?} depends on unknown task {:?}",
+                    task_name, dep
+                )));
+            }
+            deps.insert(dep.to_owned());
+        }
+        let haystack = serde_json::to_string(&(&task.commands, &task.env))?;
+        let variables = variable_names(&haystack);
+        for other_name in tasks.keys().filter(|other_name| *other_name != task_name) {
+            let prefix = format!("{}__{}", pipeline_name, other_name);
+            if variables
+                .iter()
+                .filter_map(|variable| variable.strip_prefix(prefix.as_str()))
+                .any(|rest| {
+                    rest == "_CMD_CONTENT"
+                        || rest
+                            .strip_prefix('[')
+                            .and_then(|rest| rest.strip_suffix("]_CMD_CONTENT"))
+                            .is_some_and(|idx| idx.parse::<usize>().is_ok())
+                })
+            {
+                deps.insert(other_name.to_owned());
+            }
+        }
+        graph.insert(task_name.to_owned(), deps);
+    }
+    Ok(graph)
+}
+
+/// Names of the variables `s` refers to, as `${NAME}`, `${NAME:default}` or `$NAME`
+fn variable_names(s: &str) -> indexmap::IndexSet<&str> {
+    let mut names = indexmap::IndexSet::<&str>::new();
+    for (i, _) in s.match_indices('$') {
+        let rest = &s[i + 1..];
+        let name = match rest.strip_prefix('{') {
+            Some(braced) => braced.find(['}', ':']).map_or("", |end| &braced[..end]),
+            None => {
+                let end = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                &rest[..end]
+            }
+        };
+        if !name.is_empty() {
+            names.insert(name);
+        }
+    }
+    names
+}
+
+/// Group tasks into waves: every task of a wave only depends on tasks of earlier waves.
+/// Within a wave tasks keep their declaration order.
+pub fn waves(
+    graph: &indexmap::IndexMap<String, indexmap::IndexSet<String>>,
+) -> Result<Vec<Vec<String>>, VermanSchemaError> {
+    let mut done = indexmap::IndexSet::<String>::with_capacity(graph.len());
+    let mut waves = Vec::<Vec<String>>::new();
+    while done.len() < graph.len() {
+        let wave: Vec<String> = graph
+            .iter()
+            .filter(|(name, deps)| !done.contains(*name) && deps.iter().all(|d| done.contains(d)))
+            .map(|(name, _)| name.to_owned())
+            .collect();
+        if wave.is_empty() {
+            let stuck: Vec<&String> = graph.keys().filter(|name| !done.contains(*name)).collect();
+            return Err(VermanSchemaError::Task(format!(
+                "dependency cycle between tasks {:?}",
+                stuck
+            )));
+        }
+        done.extend(wave.iter().cloned());
+        waves.push(wave);
+    }
+    Ok(waves)
+}
+
+/// `task_name`'s dependencies, direct or not
+fn transitive_dependencies<'a>(
+    graph: &'a indexmap::IndexMap<String, indexmap::IndexSet<String>>,
+    task_name: &str,
+) -> indexmap::IndexSet<&'a String> {
+    let mut found = indexmap::IndexSet::<&String>::new();
+    let mut stack: Vec<&String> = graph[task_name].iter().collect();
+    while let Some(dep) = stack.pop() {
+        if found.insert(dep) {
+            stack.extend(graph[dep].iter());
+        }
+    }
+    found
+}
+
+/// Run `tasks` as a DAG: each task starts as soon as the ones it depends on finished (up to
+/// `options.parallelism` at once).
+/// Results are merged in the order of `waves`, never in completion order: a task sees the env
+/// merged from its (transitive) dependencies only, and the resulting env is the same on every
+/// run.
+pub async fn process_tasks_as_dag(
+    pipeline_name: &String,
+    tasks: &indexmap::IndexMap<String, Task>,
+    mut shared_env_for_tasks: indexmap::IndexMap<String, serde_json::Value>,
+    options: &SchedulerOptions,
+    process_options: &ProcessOptions,
+) -> Result<CommonContent, VermanSchemaError> {
+    let graph = dependency_graph(pipeline_name, tasks)?;
+    /* also rejects cycles before anything runs */
+    let order: Vec<String> = waves(&graph)?.into_iter().flatten().collect();
+    let mut started = indexmap::IndexSet::<&String>::with_capacity(graph.len());
+    let mut results = indexmap::IndexMap::<&String, CommonContent>::with_capacity(graph.len());
+    let mut running = futures::stream::FuturesUnordered::new();
+    while results.len() < graph.len() {
+        for (task_name, deps) in graph.iter() {
+            if running.len() >= options.parallelism.max(1) {
+                break;
+            }
+            if started.contains(task_name) || !deps.iter().all(|dep| results.contains_key(dep)) {
+                continue;
+            }
+            let (i, task_name, task) = tasks.get_full(task_name).unwrap();
+            log::info!("Executing task {:#?}", task_name);
+            let mut env_for_task = shared_env_for_tasks.clone();
+            let dependencies = transitive_dependencies(&graph, task_name);
+            for dep in order.iter().filter(|name| dependencies.contains(name)) {
+                merge_env(&mut env_for_task, &tasks[dep].env);
+                merge_task_result(dep, results[dep].to_owned(), &mut env_for_task);
+            }
+            let task = prepare_task(task_name, task, &mut env_for_task);
+            started.insert(task_name);
+            running.push(async move {
+                let result = task
+                    .process(pipeline_name, task_name, i, process_options)
+                    .await;
+                (task_name, result)
+            });
+        }
+        let (task_name, result) = running
+            .next()
+            .await
+            .expect("a task is ready whenever the graph has no cycle");
+        results.insert(task_name, result?);
+    }
+    for task_name in order.iter() {
+        merge_env(&mut shared_env_for_tasks, &tasks[task_name].env);
+        merge_task_result(
+            task_name,
+            results.swap_remove(task_name).unwrap(),
+            &mut shared_env_for_tasks,
+        );
+    }
+    Ok(CommonContent {
+        env: Some(shared_env_for_tasks.to_owned()),
+        ..CommonContent::default()
+    })
+}
+
+#[cfg(test)]
+#[path = "scheduler_test.rs"]
+mod tests;
@@ -0,0 +1,157 @@
+use super::*;
+use crate::commands::command::{Command, CommandKey};
+use crate::commands::exec::ExecKey;
+use crate::models::{CommonContent, ExecArgs, ExecCommandArgs, Pipeline, Task};
+use crate::task::task::TaskKey;
+
+fn echo_task(content: &str, depends_on: Option<Vec<String>>) -> Task {
+    Task {
+        commands: vec![Command::Echo(CommonContent {
+            content: Some(serde_json::Value::String(String::from(content))),
+            env: None,
+            ..CommonContent::default()
+        })],
+        depends_on,
+        ..Task::default()
+    }
+}
+
+#[test]
+fn dependency_graph_test() {
+    let pipeline_name = String::from("p");
+    let tasks = indexmap::indexmap! {
+        String::from("c") => echo_task("${p__a_CMD_CONTENT} ${p__b[1]_CMD_CONTENT}", None),
+        String::from("a") => echo_task("a", None),
+        String::from("b") => echo_task("b", Some(vec![String::from("a")])),
+    };
+    let graph = dependency_graph(&pipeline_name, &tasks).unwrap();
+    assert_eq!(
+        graph,
+        indexmap::indexmap! {
+            String::from("c") => indexmap::indexset! { String::from("a"), String::from("b") },
+            String::from("a") => indexmap::IndexSet::new(),
+            String::from("b") => indexmap::indexset! { String::from("a") },
+        }
+    );
+    assert_eq!(
+        waves(&graph).unwrap(),
+        vec![
+            vec![String::from("a")],
+            vec![String::from("b")],
+            vec![String::from("c")]
+        ]
+    );
+}
+
+#[test]
+fn dependency_graph_exact_names_test() {
+    let pipeline_name = String::from("p");
+    let tasks = indexmap::indexmap! {
+        String::from("a") => echo_task("a", None),
+        String::from("ab") => echo_task("ab", None),
+        String::from("a[") => echo_task("a[", None),
+        String::from("uses_ab") => echo_task("${p__ab_CMD_CONTENT} $p__ab[0]_CMD_CONTENT", None),
+        String::from("uses_a") => echo_task("${p__a[0]_CMD_CONTENT:-} $p__a_CMD_CONTENT", None),
+    };
+    let graph = dependency_graph(&pipeline_name, &tasks).unwrap();
+    assert_eq!(graph["uses_ab"], indexmap::indexset! { String::from("ab") });
+    assert_eq!(graph["uses_a"], indexmap::indexset! { String::from("a") });
+}
+
+#[test]
+fn dependency_cycle_test() {
+    let pipeline_name = String::from("p");
+    let tasks = indexmap::indexmap! {
+        String::from("a") => echo_task("a", Some(vec![String::from("b")])),
+        String::from("b") => echo_task("${p__a_CMD_CONTENT}", None),
+    };
+    let graph = dependency_graph(&pipeline_name, &tasks).unwrap();
+    assert!(waves(&graph).is_err());
+}
+
+#[test]
+fn unknown_dependency_test() {
+    let tasks = indexmap::indexmap! {
+        String::from("a") => echo_task("a", Some(vec![String::from("nope")])),
+    };
+    assert!(dependency_graph(&String::from("p"), &tasks).is_err());
+}
+
+#[tokio::test]
+async fn dag_pipeline_test() {
+    let pipeline = Pipeline {
+        name: String::from("p"),
+        tasks: Some(indexmap::indexmap! {
+            String::from("joined") => echo_task("${p__first_CMD_CONTENT} and ${p__second_CMD_CONTENT}", None),
+            String::from("first") => echo_task("one", None),
+            String::from("second") => echo_task("two", None),
+        }),
+        ..Pipeline::default()
+    };
+    let env = pipeline
+        .process_with_scheduler(
+            &SchedulerOptions { parallelism: 2 },
+            &ProcessOptions::default(),
+        )
+        .await
+        .unwrap()
+        .env
+        .unwrap();
+    assert_eq!(
+        env.get(CommandKey::PreviousContent.to_string().as_str())
+            .unwrap(),
+        &serde_json::Value::String(String::from("one and two"))
+    );
+    assert_eq!(
+        env.get(TaskKey::PreviousName.to_string().as_str()).unwrap(),
+        &serde_json::Value::String(String::from("joined"))
+    );
+}
+
+/// Writes `value` to `EXEC_STDERR` after `sleep` seconds
+fn sleeping_task(value: &str, sleep: &str) -> Task {
+    Task {
+        commands: vec![Command::Exec(ExecCommandArgs {
+            args: ExecArgs {
+                program: String::from("/bin/sh"),
+                args: vec![
+                    String::from("-c"),
+                    format!("sleep {}; echo {} >&2", sleep, value),
+                ],
+                cwd: None,
+            },
+            ..ExecCommandArgs::default()
+        })],
+        ..Task::default()
+    }
+}
+
+#[tokio::test]
+async fn dag_deterministic_env_test() {
+    for (first_sleep, second_sleep) in [("0.3", "0"), ("0", "0.3"), ("0.3", "0")] {
+        let tasks = indexmap::indexmap! {
+            String::from("first") => sleeping_task("first", first_sleep),
+            String::from("second") => sleeping_task("second", second_sleep),
+        };
+        let env = process_tasks_as_dag(
+            &String::from("p"),
+            &tasks,
+            indexmap::IndexMap::new(),
+            &SchedulerOptions { parallelism: 2 },
+            &ProcessOptions::default(),
+        )
+        .await
+        .unwrap()
+        .env
+        .unwrap();
+        /* declaration order, whichever finished last */
+        assert_eq!(
+            env.get(ExecKey::Stderr.to_string().as_str()).unwrap(),
+            &serde_json::Value::String(String::from("second\n"))
+        );
+        assert_eq!(
+            env.get(TaskKey::PreviousName.to_string().as_str()).unwrap(),
+            &serde_json::Value::String(String::from("second"))
+        );
+    }
+}
@@ -0,0 +1,28 @@
+use crate::errors::{SchemaViolation, VermanSchemaError};
+use crate::models::JsonSchema;
+
+/// Validate `instance` against `schema`, collecting every violation rather than the first.
+/// `location` says where the schema came from, e.g., `"task0.input_schema"`.
+pub(crate) fn validate_json_schema(
+    schema: &JsonSchema,
+    instance: &serde_json::Value,
+    location: String,
+) -> Result<(), VermanSchemaError> {
+    let validator = jsonschema::validator_for(schema)
+        .map_err(|e| VermanSchemaError::JsonSchemaError(format!("{}: {}", location, e)))?;
+    let violations: Vec<SchemaViolation> = validator
+        .iter_errors(instance)
+        .map(|e| SchemaViolation {
+            instance_path: e.instance_path.to_string(),
+            message: e.to_string(),
+        })
+        .collect();
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(VermanSchemaError::SchemaValidation {
+            location,
+            violations,
+        })
+    }
+}
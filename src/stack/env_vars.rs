@@ -0,0 +1,110 @@
+use crate::verman_schema::{Component, Root, ServerConfiguration};
+
+/// Layer an `env_vars` entry came from, lowest priority first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, derive_more::Display)]
+pub enum EnvVarsSource {
+    #[display("system")]
+    System,
+
+    #[display("root")]
+    Root,
+
+    #[display("server_configuration")]
+    ServerConfiguration,
+
+    #[display("component")]
+    Component,
+}
+
+/// What `env_vars` are resolved for
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EnvVarsOf<'a> {
+    ServerConfiguration(&'a ServerConfiguration),
+    Component(&'a Component),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResolvedEnvVar {
+    pub value: String,
+    pub source: EnvVarsSource,
+    /// Values of lower priority layers this one overrides, lowest first
+    pub overridden: Vec<(EnvVarsSource, String)>,
+}
+
+impl Root {
+    /// Effective `env_vars` of a `ServerConfiguration` or `Component`, following the priority of
+    /// the `env_vars` docs: `ServerConfiguration` | `Component`; `Root`; `system`.
+    /// Keys keep the order they were first seen in, lowest priority layer first.
+    pub fn resolve_env_vars(
+        &self,
+        of: EnvVarsOf<'_>,
+        system: impl IntoIterator<Item = (String, String)>,
+    ) -> indexmap::IndexMap<String, ResolvedEnvVar> {
+        let (source, env_vars) = match of {
+            EnvVarsOf::ServerConfiguration(server_configuration) => (
+                EnvVarsSource::ServerConfiguration,
+                &server_configuration.env_vars,
+            ),
+            EnvVarsOf::Component(component) => (EnvVarsSource::Component, &component.env_vars),
+        };
+        let mut resolved = indexmap::IndexMap::<String, ResolvedEnvVar>::new();
+        let layers = system
+            .into_iter()
+            .map(|kv| (EnvVarsSource::System, kv))
+            .chain(
+                self.env_vars
+                    .iter()
+                    .flatten()
+                    .map(|(k, v)| (EnvVarsSource::Root, (k.to_owned(), v.to_owned()))),
+            )
+            .chain(
+                env_vars
+                    .iter()
+                    .flatten()
+                    .map(|(k, v)| (source, (k.to_owned(), v.to_owned()))),
+            );
+        for (source, (name, value)) in layers {
+            match resolved.get_mut(&name) {
+                Some(existing) => {
+                    let previous = std::mem::replace(&mut existing.value, value);
+                    existing.overridden.push((existing.source, previous));
+                    existing.source = source;
+                }
+                None => {
+                    resolved.insert(
+                        name,
+                        ResolvedEnvVar {
+                            value,
+                            source,
+                            overridden: Vec::new(),
+                        },
+                    );
+                }
+            }
+        }
+        resolved
+    }
+
+    /// `resolve_env_vars` with this process' environment as the system layer; non UTF-8
+    /// variables are skipped
+    pub fn resolve_env_vars_on_host(
+        &self,
+        of: EnvVarsOf<'_>,
+    ) -> indexmap::IndexMap<String, ResolvedEnvVar> {
+        self.resolve_env_vars(of, crate::system_env::process_vars())
+    }
+}
+
+/// Just the values of `resolved`
+pub fn values(
+    resolved: &indexmap::IndexMap<String, ResolvedEnvVar>,
+) -> indexmap::IndexMap<String, String> {
+    resolved
+        .iter()
+        .map(|(name, resolved_env_var)| (name.to_owned(), resolved_env_var.value.to_owned()))
+        .collect()
+}
+
+#[cfg(test)]
+#[path = "env_vars_test.rs"]
+mod tests;
@@ -0,0 +1,59 @@
+use super::*;
+
+fn env_vars(kvs: &[(&str, &str)]) -> Option<indexmap::IndexMap<String, String>> {
+    Some(
+        kvs.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+    )
+}
+
+#[test]
+fn resolve_env_vars_test() {
+    let root = Root {
+        env_vars: env_vars(&[("PORT", "8080"), ("HOST", "root.local")]),
+        ..Root::default()
+    };
+    let server_configuration = ServerConfiguration {
+        kind: String::from("postgres"),
+        env_vars: env_vars(&[("PORT", "5432")]),
+        ..ServerConfiguration::default()
+    };
+    let system = vec![
+        (String::from("PORT"), String::from("80")),
+        (String::from("HOME"), String::from("/root")),
+    ];
+    let resolved = root.resolve_env_vars(
+        EnvVarsOf::ServerConfiguration(&server_configuration),
+        system.clone(),
+    );
+    assert_eq!(
+        values(&resolved),
+        indexmap::indexmap! {
+            String::from("PORT") => String::from("5432"),
+            String::from("HOME") => String::from("/root"),
+            String::from("HOST") => String::from("root.local"),
+        }
+    );
+    assert_eq!(
+        resolved["PORT"],
+        ResolvedEnvVar {
+            value: String::from("5432"),
+            source: EnvVarsSource::ServerConfiguration,
+            overridden: vec![
+                (EnvVarsSource::System, String::from("80")),
+                (EnvVarsSource::Root, String::from("8080")),
+            ],
+        }
+    );
+    assert_eq!(resolved["HOME"].source, EnvVarsSource::System);
+    assert_eq!(resolved["HOST"].source, EnvVarsSource::Root);
+
+    let component = Component {
+        env_vars: env_vars(&[("HOST", "component.local")]),
+        ..Component::default()
+    };
+    let resolved = root.resolve_env_vars(EnvVarsOf::Component(&component), system);
+    assert_eq!(resolved["HOST"].source, EnvVarsSource::Component);
+    assert_eq!(resolved["PORT"].source, EnvVarsSource::Root);
+}
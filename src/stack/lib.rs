@@ -0,0 +1,7 @@
+pub mod env_vars;
+pub mod mount_action;
+pub mod planner;
+pub mod routing;
+pub mod server;
+pub mod version;
+pub mod when;
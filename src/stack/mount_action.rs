@@ -0,0 +1,85 @@
+use crate::errors::VermanSchemaError;
+use crate::models::JsonSchema;
+use crate::schema_validation::validate_json_schema;
+use crate::verman_schema::Root;
+
+/// Known `Mount.action`s, each with the JSON Schema its `action_args` must match
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MountActionRegistry {
+    pub schemas: indexmap::IndexMap<String, JsonSchema>,
+}
+
+impl MountActionRegistry {
+    pub fn register(&mut self, action: impl Into<String>, schema: JsonSchema) -> &mut Self {
+        self.schemas.insert(action.into(), schema);
+        self
+    }
+
+    /// Register the schema found at `uri`: a `file:` URI (percent-encoded, with an empty or
+    /// `localhost` host) or a local path, in JSON, TOML or YAML
+    pub fn register_uri(
+        &mut self,
+        action: impl Into<String>,
+        uri: &str,
+    ) -> Result<&mut Self, VermanSchemaError> {
+        let action = action.into();
+        let unresolved = || VermanSchemaError::UnresolvedRef {
+            reference: uri.to_owned(),
+            location: format!("schema of mount action {:?}, only local files", action),
+        };
+        let path = if uri.starts_with("file:") {
+            url::Url::parse(uri)
+                .ok()
+                .and_then(|url| url.to_file_path().ok())
+                .ok_or_else(unresolved)?
+        } else if uri.contains("://") {
+            return Err(unresolved());
+        } else {
+            std::path::PathBuf::from(uri)
+        };
+        let schema: JsonSchema = crate::loader::loader::from_path(path)?;
+        Ok(self.register(action, schema))
+    }
+
+    /// Check every `Mount` of `root`: its `action` is registered and its `action_args` (`null`
+    /// when unset) match that action's schema.
+    /// Locations are JSON pointers, e.g., `#/component/0/mounts/1/action_args`.
+    pub fn validate(&self, root: &Root) -> Result<(), VermanSchemaError> {
+        for (i, component) in root.component.iter().enumerate() {
+            for (j, mount) in component.mounts.iter().flatten().enumerate() {
+                let location = format!("#/component/{}/mounts/{}", i, j);
+                let schema = self.schemas.get(&mount.action).ok_or_else(|| {
+                    VermanSchemaError::UnknownMountAction {
+                        action: mount.action.to_owned(),
+                        location: format!("{}/action", location),
+                    }
+                })?;
+                validate_json_schema(
+                    schema,
+                    mount
+                        .action_args
+                        .as_ref()
+                        .unwrap_or(&serde_json::Value::Null),
+                    format!("{}/action_args", location),
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Root {
+    /// Load with `loader::from_path`, then validate `Mount`s with `registry`
+    pub fn from_path(
+        path: impl AsRef<std::path::Path>,
+        registry: &MountActionRegistry,
+    ) -> Result<Self, VermanSchemaError> {
+        let root: Root = crate::loader::loader::from_path(path)?;
+        registry.validate(&root)?;
+        Ok(root)
+    }
+}
+
+#[cfg(test)]
+#[path = "mount_action_test.rs"]
+mod tests;
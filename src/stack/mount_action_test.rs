@@ -0,0 +1,78 @@
+use super::*;
+use crate::verman_schema::{Component, Mount};
+
+fn root(action_args: serde_json::Value) -> Root {
+    Root {
+        component: vec![Component {
+            mounts: Some(vec![
+                Mount {
+                    action: String::from("noop"),
+                    ..Mount::default()
+                },
+                Mount {
+                    action: String::from("copy"),
+                    action_args: Some(action_args),
+                    ..Mount::default()
+                },
+            ]),
+            ..Component::default()
+        }],
+        ..Root::default()
+    }
+}
+
+fn registry() -> MountActionRegistry {
+    let schema_path =
+        std::env::temp_dir().join(format!("verman copy schema {}.yaml", std::process::id()));
+    std::fs::write(
+        &schema_path,
+        "type: object\nrequired: [dst]\nproperties:\n  dst: {type: string}\n",
+    )
+    .unwrap();
+    let mut registry = MountActionRegistry::default();
+    registry
+        .register("noop", serde_json::json!({"type": "null"}))
+        .register_uri(
+            "copy",
+            &format!("file://localhost{}", schema_path.display()).replace(' ', "%20"),
+        )
+        .unwrap();
+    registry
+}
+
+#[test]
+fn mount_action_validate_test() {
+    registry()
+        .validate(&root(serde_json::json!({"dst": "/opt"})))
+        .unwrap();
+    match registry().validate(&root(serde_json::json!({"dst": 5}))) {
+        Err(VermanSchemaError::SchemaValidation {
+            location,
+            violations,
+        }) => {
+            assert_eq!(location, "#/component/0/mounts/1/action_args");
+            assert_eq!(violations[0].instance_path, "/dst");
+        }
+        other => panic!("expected `SchemaValidation`, got {:?}", other),
+    }
+}
+
+#[test]
+fn mount_action_unknown_test() {
+    match MountActionRegistry::default().validate(&root(serde_json::Value::Null)) {
+        Err(VermanSchemaError::UnknownMountAction { action, location }) => {
+            assert_eq!(action, "noop");
+            assert_eq!(location, "#/component/0/mounts/0/action");
+        }
+        other => panic!("expected `UnknownMountAction`, got {:?}", other),
+    }
+    for uri in [
+        "https://example.com/copy.json",
+        "file://example.com/copy.json",
+    ] {
+        assert!(matches!(
+            MountActionRegistry::default().register_uri("copy", uri),
+            Err(VermanSchemaError::UnresolvedRef { .. })
+        ));
+    }
+}
@@ -0,0 +1,199 @@
+use crate::errors::VermanSchemaError;
+use crate::verman_schema::{Root, State};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, derive_more::Display)]
+pub enum Verb {
+    #[display("stop")]
+    Stop,
+
+    #[display("remove")]
+    Remove,
+
+    #[display("install")]
+    Install,
+
+    #[display("start")]
+    Start,
+
+    /// Use what is already there; see `State::Untouched`
+    #[display("use")]
+    Use,
+}
+
+/// Order in which a `Plan` runs its `Action`s: tear down before setting up
+const PHASES: [Verb; 5] = [
+    Verb::Stop,
+    Verb::Remove,
+    Verb::Install,
+    Verb::Start,
+    Verb::Use,
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Action {
+    pub verb: Verb,
+    pub kind: String,
+    /// Candidate versions from `ServerConfiguration.versions`; empty when unconstrained
+    pub versions: Vec<String>,
+    /// `State::Always`: reinstall or restart even when already installed or running
+    pub force: bool,
+    /// `State::DryRun`: only reported, see `Plan::execute`
+    pub dry_run: bool,
+}
+
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.dry_run {
+            write!(f, "[dry-run] ")?;
+        }
+        write!(f, "{} {}", self.verb, self.kind)?;
+        if !self.versions.is_empty() {
+            write!(f, " ({})", self.versions.join(", "))?;
+        }
+        if self.force {
+            write!(f, " [force]")?;
+        }
+        Ok(())
+    }
+}
+
+/// Carries out the `Action`s of a `Plan`, e.g., with a package manager
+pub trait ActionRunner {
+    fn run(
+        &mut self,
+        action: &Action,
+    ) -> impl std::future::Future<Output = Result<(), VermanSchemaError>>;
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Plan {
+    pub actions: Vec<Action>,
+}
+
+impl std::fmt::Display for Plan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, action) in self.actions.iter().enumerate() {
+            writeln!(f, "{}. {}", i + 1, action)?;
+        }
+        Ok(())
+    }
+}
+
+impl Plan {
+    /// Same `Action`s, all only reported
+    pub fn dry_run(self) -> Self {
+        Self {
+            actions: self
+                .actions
+                .into_iter()
+                .map(|action| Action {
+                    dry_run: true,
+                    ..action
+                })
+                .collect(),
+        }
+    }
+
+    /// Hand each `Action` to `runner`, in order; dry-run ones are written to `out` instead, so
+    /// they never reach the network
+    pub async fn execute(
+        &self,
+        runner: &mut impl ActionRunner,
+        out: &mut impl std::io::Write,
+    ) -> Result<(), VermanSchemaError> {
+        for action in self.actions.iter() {
+            if action.dry_run {
+                writeln!(out, "{}", action)?;
+            } else {
+                log::info!("Executing {}", action);
+                runner.run(action).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Kinds `root` knows of, in order: `stack` first, then `component` constraints
+pub fn kinds(root: &Root) -> indexmap::IndexSet<String> {
+    root.stack
+        .values()
+        .flatten()
+        .map(|server_configuration| server_configuration.kind.to_owned())
+        .chain(
+            root.component
+                .iter()
+                .flat_map(|component| component.constraints.iter())
+                .map(|constraint| constraint.kind.to_owned()),
+        )
+        .collect()
+}
+
+/// `(verb, force)`s for `state`, see the docs of `State`
+fn verbs(state: &State) -> Vec<(Verb, bool)> {
+    match state {
+        State::Always => vec![(Verb::Install, true), (Verb::Start, true)],
+        State::Graceful | State::DryRun => vec![(Verb::Install, false), (Verb::Start, false)],
+        State::Untouched => vec![(Verb::Use, false)],
+        State::Stop => vec![(Verb::Stop, false)],
+        State::Remove => vec![(Verb::Stop, false), (Verb::Remove, false)],
+    }
+}
+
+/// Turn `root` and the `State` requested per kind into a `Plan`.
+/// Kinds not in `requested` are `State::Untouched`.
+/// Stops and removals run first, in reverse kind order; then installs, starts and uses, in kind
+/// order.
+///
+/// `Root.stack_state` is not diffed against: it holds requested `State`s per verb, not what is
+/// actually installed or running, so there is no current state to compare with. Whether an
+/// `Action` without `force` has anything to do is left to the `ActionRunner`, which can probe
+/// the machine.
+pub fn plan(
+    root: &Root,
+    requested: &indexmap::IndexMap<String, State>,
+) -> Result<Plan, VermanSchemaError> {
+    let kinds = kinds(root);
+    if let Some(unknown) = requested.keys().find(|kind| !kinds.contains(*kind)) {
+        return Err(VermanSchemaError::UnknownKind(unknown.to_owned()));
+    }
+    let mut actions = Vec::<Action>::new();
+    for phase in PHASES {
+        let ordered_kinds: Vec<&String> = match phase {
+            Verb::Stop | Verb::Remove => kinds.iter().rev().collect(),
+            _ => kinds.iter().collect(),
+        };
+        for kind in ordered_kinds {
+            let state = requested.get(kind).cloned().unwrap_or_default();
+            for (verb, force) in verbs(&state) {
+                if verb != phase {
+                    continue;
+                }
+                actions.push(Action {
+                    verb,
+                    kind: kind.to_owned(),
+                    versions: versions(root, kind),
+                    force,
+                    dry_run: state == State::DryRun,
+                });
+            }
+        }
+    }
+    Ok(Plan { actions })
+}
+
+/// `ServerConfiguration.versions` of `kind`, deduplicated
+fn versions(root: &Root, kind: &str) -> Vec<String> {
+    root.stack
+        .values()
+        .flatten()
+        .filter(|server_configuration| server_configuration.kind == kind)
+        .flat_map(|server_configuration| server_configuration.versions.iter().flatten())
+        .cloned()
+        .collect::<indexmap::IndexSet<String>>()
+        .into_iter()
+        .collect()
+}
+
+#[cfg(test)]
+#[path = "planner_test.rs"]
+mod tests;
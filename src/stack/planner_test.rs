@@ -0,0 +1,99 @@
+use super::*;
+use crate::verman_schema::{Component, Constraint, ServerConfiguration};
+
+struct Recorder(Vec<String>);
+
+impl ActionRunner for Recorder {
+    async fn run(&mut self, action: &Action) -> Result<(), VermanSchemaError> {
+        self.0.push(action.to_string());
+        Ok(())
+    }
+}
+
+fn root() -> Root {
+    Root {
+        stack: indexmap::indexmap! {
+            String::from("database") => vec![ServerConfiguration {
+                kind: String::from("postgres"),
+                versions: Some(vec![String::from("16"), String::from("15")]),
+                ..ServerConfiguration::default()
+            }],
+            String::from("cache") => vec![ServerConfiguration {
+                kind: String::from("redis"),
+                ..ServerConfiguration::default()
+            }],
+        },
+        component: vec![Component {
+            constraints: vec![Constraint {
+                kind: String::from("python"),
+                ..Constraint::default()
+            }],
+            ..Component::default()
+        }],
+        ..Root::default()
+    }
+}
+
+#[test]
+fn plan_order_test() {
+    let plan = plan(
+        &root(),
+        &indexmap::indexmap! {
+            String::from("postgres") => State::Always,
+            String::from("redis") => State::Remove,
+            String::from("python") => State::Graceful,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        plan.to_string(),
+        "1. stop redis\n\
+         2. remove redis\n\
+         3. install postgres (16, 15) [force]\n\
+         4. install python\n\
+         5. start postgres (16, 15) [force]\n\
+         6. start python\n"
+    );
+}
+
+#[test]
+fn plan_untouched_and_unknown_kind_test() {
+    let plan = plan(&root(), &indexmap::IndexMap::new()).unwrap();
+    assert!(plan.actions.iter().all(|action| action.verb == Verb::Use));
+    assert_eq!(plan.actions.len(), 3);
+
+    assert!(matches!(
+        super::plan(
+            &root(),
+            &indexmap::indexmap! { String::from("mysql") => State::Always }
+        ),
+        Err(VermanSchemaError::UnknownKind(kind)) if kind == "mysql"
+    ));
+}
+
+#[tokio::test]
+async fn plan_dry_run_test() {
+    let plan = plan(
+        &root(),
+        &indexmap::indexmap! {
+            String::from("postgres") => State::DryRun,
+            String::from("redis") => State::Stop,
+        },
+    )
+    .unwrap();
+    let mut recorder = Recorder(Vec::new());
+    let mut out = Vec::<u8>::new();
+    plan.execute(&mut recorder, &mut out).await.unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "[dry-run] install postgres (16, 15)\n[dry-run] start postgres (16, 15)\n"
+    );
+    assert_eq!(recorder.0, vec!["stop redis", "use python"]);
+
+    let mut recorder = Recorder(Vec::new());
+    plan.dry_run()
+        .execute(&mut recorder, &mut std::io::sink())
+        .await
+        .unwrap();
+    assert!(recorder.0.is_empty());
+}
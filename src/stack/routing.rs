@@ -0,0 +1,392 @@
+use crate::errors::VermanSchemaError;
+use crate::verman_schema::Root;
+
+const DEFAULT_UPSTREAM_HOST: &str = "127.0.0.1";
+
+/// Parsed `ProtocolConfiguration.certificate_vendor`
+#[derive(Clone, Debug, PartialEq, Eq, Hash, derive_more::Display)]
+pub enum CertificateVendor {
+    /// Generated locally, works offline; the default for `https`
+    #[display("SelfSigned")]
+    SelfSigned,
+
+    /// ACME; Caddy obtains these itself, for nginx they are expected where `certbot` puts them
+    #[display("LetsEncrypt")]
+    LetsEncrypt,
+
+    /// Certificate and key are expected in the certificates directory
+    #[display("{_0}")]
+    Other(String),
+}
+
+impl From<&str> for CertificateVendor {
+    fn from(vendor: &str) -> Self {
+        match vendor
+            .to_ascii_lowercase()
+            .replace(['-', '_', ' '], "")
+            .as_str()
+        {
+            "selfsigned" | "local" => Self::SelfSigned,
+            "letsencrypt" => Self::LetsEncrypt,
+            _ => Self::Other(vendor.to_owned()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, derive_more::Display)]
+pub enum ProxyFlavor {
+    #[display("nginx")]
+    Nginx,
+
+    #[display("caddy")]
+    Caddy,
+}
+
+/// `https://{host}{path}` (or `http`) proxied to `upstream`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Route {
+    pub host: String,
+    pub protocol: String,
+    /// `/{service}/`, the key of the service in `Root.stack`
+    pub path: String,
+    pub service: String,
+    pub kind: String,
+    /// `host:port`
+    pub upstream: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Certificate {
+    pub host: String,
+    pub vendor: CertificateVendor,
+    pub cert_path: std::path::PathBuf,
+    pub key_path: std::path::PathBuf,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RoutingPlan {
+    pub routes: Vec<Route>,
+    /// One per `https` host
+    pub certificates: Vec<Certificate>,
+}
+
+impl RoutingPlan {
+    /// Route every `stack_routing` host name to every service of `stack`.
+    /// A service's upstream is `{KIND}_HOST:{KIND}_PORT` from its `env_vars`, as exported by
+    /// `server::select_server`; `{KIND}_HOST` defaults to `127.0.0.1` and services without
+    /// `{KIND}_PORT` are skipped.
+    /// Everything ends up verbatim in the proxy configuration (and certificates of vendors other
+    /// than LetsEncrypt live in `cert_dir`, named after the host), so host names and `{KIND}_HOST`
+    /// that aren't DNS names or IP addresses err with `InvalidHost`, and service keys that aren't
+    /// `[A-Za-z0-9_-]+` or non-numeric `{KIND}_PORT` with `InvalidRoute`.
+    pub fn plan(root: &Root, cert_dir: &std::path::Path) -> Result<Self, VermanSchemaError> {
+        let mut plan = Self::default();
+        for protocol_configuration in root.stack_routing.iter() {
+            let host =
+                protocol_configuration
+                    .name
+                    .to_owned()
+                    .ok_or(VermanSchemaError::NotFound(
+                        "`name` of `stack_routing` entry",
+                    ))?;
+            if !is_valid_host(&host) {
+                return Err(VermanSchemaError::InvalidHost(host));
+            }
+            let protocol = protocol_configuration
+                .protocol
+                .to_owned()
+                .unwrap_or_else(|| String::from("https"));
+            if protocol != "http" && protocol != "https" {
+                return Err(VermanSchemaError::NotInstalled(format!(
+                    "{:?} routing of {:?}, only `http` and `https` are supported",
+                    protocol, host
+                )));
+            }
+            for (service, server_configurations) in root.stack.iter() {
+                if service.is_empty()
+                    || !service
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                {
+                    return Err(VermanSchemaError::InvalidRoute {
+                        service: service.to_owned(),
+                        value: service.to_owned(),
+                    });
+                }
+                for server_configuration in server_configurations {
+                    let prefix = server_configuration
+                        .kind
+                        .to_uppercase()
+                        .replace(['-', '.', ' '], "_");
+                    let env_vars = server_configuration.env_vars.to_owned().unwrap_or_default();
+                    let port = match env_vars.get(&format!("{}_PORT", prefix)) {
+                        Some(port) => port,
+                        None => {
+                            log::warn!("No {}_PORT for {}, not routing it", prefix, service);
+                            continue;
+                        }
+                    };
+                    if port.parse::<u16>().is_err() {
+                        return Err(VermanSchemaError::InvalidRoute {
+                            service: service.to_owned(),
+                            value: port.to_owned(),
+                        });
+                    }
+                    let upstream_host = env_vars
+                        .get(&format!("{}_HOST", prefix))
+                        .map(String::as_str)
+                        .unwrap_or(DEFAULT_UPSTREAM_HOST);
+                    if !is_valid_host(upstream_host) {
+                        return Err(VermanSchemaError::InvalidHost(upstream_host.to_owned()));
+                    }
+                    plan.routes.push(Route {
+                        host: host.to_owned(),
+                        protocol: protocol.to_owned(),
+                        path: format!("/{}/", service),
+                        service: service.to_owned(),
+                        kind: server_configuration.kind.to_owned(),
+                        upstream: format!("{}:{}", upstream_host, port),
+                    });
+                }
+            }
+            if protocol == "https" {
+                let vendor = protocol_configuration
+                    .certificate_vendor
+                    .as_deref()
+                    .map(CertificateVendor::from)
+                    .unwrap_or(CertificateVendor::SelfSigned);
+                let (cert_path, key_path) = match vendor {
+                    CertificateVendor::LetsEncrypt => {
+                        let live = std::path::Path::new("/etc/letsencrypt/live").join(&host);
+                        (live.join("fullchain.pem"), live.join("privkey.pem"))
+                    }
+                    _ => (
+                        cert_dir.join(format!("{}.crt", host)),
+                        cert_dir.join(format!("{}.key", host)),
+                    ),
+                };
+                plan.certificates.push(Certificate {
+                    host,
+                    vendor,
+                    cert_path,
+                    key_path,
+                });
+            }
+        }
+        Ok(plan)
+    }
+
+    /// Hosts needing a certificate, grouped by vendor
+    pub fn certificates_by_vendor(
+        &self,
+    ) -> indexmap::IndexMap<CertificateVendor, Vec<&Certificate>> {
+        let mut by_vendor = indexmap::IndexMap::<CertificateVendor, Vec<&Certificate>>::new();
+        for certificate in self.certificates.iter() {
+            by_vendor
+                .entry(certificate.vendor.to_owned())
+                .or_default()
+                .push(certificate);
+        }
+        by_vendor
+    }
+
+    fn certificate(&self, host: &str) -> Option<&Certificate> {
+        self.certificates
+            .iter()
+            .find(|certificate| certificate.host == host)
+    }
+
+    /// Reverse-proxy configuration, one server block per host and one location per path.
+    /// Services with several `ServerConfiguration`s are load-balanced over their upstreams, with
+    /// an nginx `upstream` block.
+    pub fn render(&self, flavor: ProxyFlavor) -> String {
+        /* (protocol, host) -> path -> upstreams */
+        let mut hosts = indexmap::IndexMap::<
+            (&str, &str),
+            indexmap::IndexMap<&str, indexmap::IndexSet<&str>>,
+        >::new();
+        for route in self.routes.iter() {
+            hosts
+                .entry((route.protocol.as_str(), route.host.as_str()))
+                .or_default()
+                .entry(route.path.as_str())
+                .or_default()
+                .insert(route.upstream.as_str());
+        }
+        let mut out = String::new();
+        if flavor == ProxyFlavor::Nginx {
+            let mut upstream_blocks =
+                indexmap::IndexMap::<String, &indexmap::IndexSet<&str>>::new();
+            for (path, upstreams) in hosts.values().flatten() {
+                if upstreams.len() > 1 {
+                    upstream_blocks.insert(upstream_name(path), upstreams);
+                }
+            }
+            for (name, upstreams) in upstream_blocks {
+                out.push_str(&format!("upstream {} {{\n", name));
+                for upstream in upstreams {
+                    out.push_str(&format!("    server {};\n", upstream));
+                }
+                out.push_str("}\n\n");
+            }
+        }
+        for ((protocol, host), routes) in hosts {
+            let certificate = self.certificate(host).filter(|_| protocol == "https");
+            match flavor {
+                ProxyFlavor::Nginx => {
+                    out.push_str("server {\n");
+                    match certificate {
+                        Some(certificate) => {
+                            out.push_str("    listen 443 ssl;\n");
+                            out.push_str(&format!("    server_name {};\n", host));
+                            out.push_str(&format!(
+                                "    ssl_certificate {};\n    ssl_certificate_key {};\n",
+                                certificate.cert_path.display(),
+                                certificate.key_path.display()
+                            ));
+                        }
+                        None => {
+                            out.push_str("    listen 80;\n");
+                            out.push_str(&format!("    server_name {};\n", host));
+                        }
+                    }
+                    for (path, upstreams) in routes.iter() {
+                        let upstream = match upstreams.len() {
+                            1 => upstreams[0].to_owned(),
+                            _ => upstream_name(path),
+                        };
+                        out.push_str(&format!(
+                            "\n    location {} {{\n        proxy_pass http://{}/;\n        proxy_set_header Host $host;\n    }}\n",
+                            path, upstream
+                        ));
+                    }
+                    out.push_str("}\n");
+                }
+                ProxyFlavor::Caddy => {
+                    out.push_str(&format!("{}://{} {{\n", protocol, host));
+                    if let Some(certificate) = certificate {
+                        if certificate.vendor != CertificateVendor::LetsEncrypt {
+                            out.push_str(&format!(
+                                "    tls {} {}\n",
+                                certificate.cert_path.display(),
+                                certificate.key_path.display()
+                            ));
+                        }
+                    }
+                    for (path, upstreams) in routes.iter() {
+                        out.push_str(&format!(
+                            "\n    handle_path {}* {{\n        reverse_proxy {}\n    }}\n",
+                            path,
+                            upstreams.iter().copied().collect::<Vec<&str>>().join(" ")
+                        ));
+                    }
+                    out.push_str("}\n");
+                }
+            }
+        }
+        out
+    }
+
+    /// `render` into the local file `path`
+    pub fn write_proxy_config(
+        &self,
+        flavor: ProxyFlavor,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<(), VermanSchemaError> {
+        Ok(std::fs::write(path, self.render(flavor))?)
+    }
+
+    /// Generate the `SelfSigned` certificates that don't exist yet, offline.
+    /// Returns the hosts whose certificate was generated.
+    pub fn issue_self_signed(&self) -> Result<Vec<String>, VermanSchemaError> {
+        let mut issued = Vec::<String>::new();
+        for certificate in self.certificates.iter() {
+            if certificate.vendor != CertificateVendor::SelfSigned
+                || (certificate.cert_path.exists() && certificate.key_path.exists())
+            {
+                continue;
+            }
+            let (cert_pem, key_pem) = self_signed_pem(&certificate.host)?;
+            for path in [&certificate.cert_path, &certificate.key_path] {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+            }
+            std::fs::write(&certificate.cert_path, cert_pem)?;
+            write_private(&certificate.key_path, key_pem.as_bytes())?;
+            issued.push(certificate.host.to_owned());
+        }
+        Ok(issued)
+    }
+}
+
+/// Whether `host` is an IP address (IPv6 in brackets, as in URLs) or a DNS name, i.e.,
+/// dot-separated labels of ASCII letters, digits and `-` that neither start nor end with `-`
+fn is_valid_host(host: &str) -> bool {
+    if host.parse::<std::net::Ipv4Addr>().is_ok()
+        || host
+            .strip_prefix('[')
+            .and_then(|host| host.strip_suffix(']'))
+            .is_some_and(|host| host.parse::<std::net::Ipv6Addr>().is_ok())
+    {
+        return true;
+    }
+    let name = host.strip_suffix('.').unwrap_or(host);
+    !name.is_empty()
+        && name.len() <= 253
+        && name.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// nginx `upstream` name of the service at `path`
+fn upstream_name(path: &str) -> String {
+    format!(
+        "verman_{}",
+        path.trim_matches('/')
+            .replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+    )
+}
+
+/// Write `contents` to `path`, readable and writable by the owner only (on Unix)
+fn write_private(path: &std::path::Path, contents: &[u8]) -> Result<(), VermanSchemaError> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        /* `mode` only applies to new files */
+        if path.exists() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    std::io::Write::write_all(&mut options.open(path)?, contents)?;
+    Ok(())
+}
+
+/// PEM certificate and key for `host`
+#[cfg(feature = "tls")]
+fn self_signed_pem(host: &str) -> Result<(String, String), VermanSchemaError> {
+    let certified_key = rcgen::generate_simple_self_signed(vec![host.to_owned()])?;
+    Ok((
+        certified_key.cert.pem(),
+        certified_key.key_pair.serialize_pem(),
+    ))
+}
+
+#[cfg(not(feature = "tls"))]
+fn self_signed_pem(host: &str) -> Result<(String, String), VermanSchemaError> {
+    Err(VermanSchemaError::NotInstalled(format!(
+        "self-signed certificate for {:?} requires feature `tls`",
+        host
+    )))
+}
+
+#[cfg(test)]
+#[path = "routing_test.rs"]
+mod tests;
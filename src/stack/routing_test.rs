@@ -0,0 +1,200 @@
+use super::*;
+use crate::verman_schema::{ProtocolConfiguration, ServerConfiguration};
+
+fn root() -> Root {
+    let server = |kind: &str, env_vars: &[(&str, &str)]| ServerConfiguration {
+        kind: kind.to_string(),
+        env_vars: Some(
+            env_vars
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        ),
+        ..ServerConfiguration::default()
+    };
+    let routing = |name: &str, protocol: &str, vendor: Option<&str>| ProtocolConfiguration {
+        name: Some(name.to_string()),
+        protocol: Some(protocol.to_string()),
+        certificate_vendor: vendor.map(str::to_string),
+    };
+    Root {
+        stack: indexmap::indexmap! {
+            String::from("api") => vec![server("node", &[("NODE_PORT", "3000")])],
+            String::from("search") => vec![server(
+                "elasticsearch",
+                &[("ELASTICSEARCH_HOST", "10.0.0.2"), ("ELASTICSEARCH_PORT", "9200")],
+            )],
+            String::from("queue") => vec![server("rabbitmq", &[])],
+        },
+        stack_routing: vec![
+            routing("localhost", "https", None),
+            routing("example.com", "https", Some("LetsEncrypt")),
+            routing("plain.local", "http", None),
+        ],
+        ..Root::default()
+    }
+}
+
+fn cert_dir() -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("verman_certs_{}", std::process::id()))
+}
+
+#[test]
+fn routing_plan_test() {
+    let plan = RoutingPlan::plan(&root(), &cert_dir()).unwrap();
+    assert_eq!(plan.routes.len(), 6);
+    assert_eq!(plan.routes[1].upstream, "10.0.0.2:9200");
+    assert_eq!(plan.routes[0].upstream, "127.0.0.1:3000");
+
+    let by_vendor = plan.certificates_by_vendor();
+    assert_eq!(
+        by_vendor
+            .keys()
+            .cloned()
+            .collect::<Vec<CertificateVendor>>(),
+        vec![
+            CertificateVendor::SelfSigned,
+            CertificateVendor::LetsEncrypt
+        ]
+    );
+    assert_eq!(
+        by_vendor[&CertificateVendor::LetsEncrypt][0].cert_path,
+        std::path::PathBuf::from("/etc/letsencrypt/live/example.com/fullchain.pem")
+    );
+}
+
+#[test]
+fn routing_render_test() {
+    let plan = RoutingPlan::plan(&root(), std::path::Path::new("/certs")).unwrap();
+    let caddy = plan.render(ProxyFlavor::Caddy);
+    assert!(caddy.starts_with(
+        "https://localhost {\n    tls /certs/localhost.crt /certs/localhost.key\n\n    handle_path /api/* {\n        reverse_proxy 127.0.0.1:3000\n    }\n"
+    ));
+    assert!(caddy.contains("https://example.com {\n\n    handle_path"));
+    assert!(caddy.contains("http://plain.local {"));
+
+    let nginx = plan.render(ProxyFlavor::Nginx);
+    assert!(nginx.contains(
+        "    listen 443 ssl;\n    server_name localhost;\n    ssl_certificate /certs/localhost.crt;\n"
+    ));
+    assert!(nginx.contains("    location /search/ {\n        proxy_pass http://10.0.0.2:9200/;\n"));
+    assert!(nginx.contains("    listen 80;\n    server_name plain.local;\n"));
+}
+
+#[test]
+fn routing_render_several_upstreams_test() {
+    let mut root = root();
+    root.stack["api"].push(ServerConfiguration {
+        kind: String::from("node"),
+        env_vars: Some(indexmap::indexmap! {
+            String::from("NODE_PORT") => String::from("3001"),
+        }),
+        ..ServerConfiguration::default()
+    });
+    let plan = RoutingPlan::plan(&root, std::path::Path::new("/certs")).unwrap();
+
+    let nginx = plan.render(ProxyFlavor::Nginx);
+    assert!(nginx.starts_with(
+        "upstream verman_api {\n    server 127.0.0.1:3000;\n    server 127.0.0.1:3001;\n}\n\n"
+    ));
+    assert_eq!(nginx.matches("upstream verman_api {").count(), 1);
+    assert_eq!(nginx.matches("    location /api/ {").count(), 3);
+    assert!(nginx.contains("        proxy_pass http://verman_api/;\n"));
+
+    let caddy = plan.render(ProxyFlavor::Caddy);
+    assert_eq!(caddy.matches("    handle_path /api/* {").count(), 3);
+    assert!(caddy.contains("        reverse_proxy 127.0.0.1:3000 127.0.0.1:3001\n"));
+}
+
+#[test]
+fn routing_invalid_host_test() {
+    for host in [
+        "../../etc/cron.d/x",
+        "a/b",
+        "a\\b",
+        "",
+        "a.com; include /etc/shadow",
+        "a.com b.com",
+        "a.com\n",
+        "a.com {",
+        "}",
+        "-a.com",
+    ] {
+        let mut root = root();
+        root.stack_routing[0].name = Some(host.to_string());
+        match RoutingPlan::plan(&root, &cert_dir()) {
+            Err(VermanSchemaError::InvalidHost(invalid)) => assert_eq!(invalid, host),
+            other => panic!("expected `InvalidHost` for {:?}, got {:?}", host, other),
+        }
+    }
+    for host in ["10.0.0.2", "[::1]", "example.com.", "my-host"] {
+        let mut root = root();
+        root.stack_routing[0].name = Some(host.to_string());
+        assert!(RoutingPlan::plan(&root, &cert_dir()).is_ok(), "{:?}", host);
+    }
+
+    let mut root = root();
+    root.stack["search"][0].env_vars.as_mut().unwrap().insert(
+        String::from("ELASTICSEARCH_HOST"),
+        String::from("10.0.0.2:1/; }"),
+    );
+    assert!(matches!(
+        RoutingPlan::plan(&root, &cert_dir()),
+        Err(VermanSchemaError::InvalidHost(_))
+    ));
+}
+
+#[test]
+fn routing_invalid_route_test() {
+    for service in ["api; }", "a b", "{api}", ""] {
+        let mut root = root();
+        let servers = root.stack.swap_remove("api").unwrap();
+        root.stack.insert(service.to_string(), servers);
+        match RoutingPlan::plan(&root, &cert_dir()) {
+            Err(VermanSchemaError::InvalidRoute {
+                service: invalid, ..
+            }) => assert_eq!(invalid, service),
+            other => panic!("expected `InvalidRoute` for {:?}, got {:?}", service, other),
+        }
+    }
+    for port in ["3000;", "3000 ", "{3000}", "70000"] {
+        let mut root = root();
+        root.stack["api"][0]
+            .env_vars
+            .as_mut()
+            .unwrap()
+            .insert(String::from("NODE_PORT"), port.to_string());
+        match RoutingPlan::plan(&root, &cert_dir()) {
+            Err(VermanSchemaError::InvalidRoute { service, value }) => {
+                assert_eq!(service, "api");
+                assert_eq!(value, port);
+            }
+            other => panic!("expected `InvalidRoute` for {:?}, got {:?}", port, other),
+        }
+    }
+}
+
+#[test]
+fn routing_issue_self_signed_test() {
+    let plan = RoutingPlan::plan(&root(), &cert_dir()).unwrap();
+    let issued = plan.issue_self_signed();
+    if cfg!(feature = "tls") {
+        assert_eq!(issued.unwrap(), vec!["localhost"]);
+        assert!(std::fs::read_to_string(cert_dir().join("localhost.crt"))
+            .unwrap()
+            .starts_with("-----BEGIN CERTIFICATE-----"));
+        #[cfg(unix)]
+        assert_eq!(
+            std::os::unix::fs::PermissionsExt::mode(
+                &std::fs::metadata(cert_dir().join("localhost.key"))
+                    .unwrap()
+                    .permissions()
+            ) & 0o777,
+            0o600
+        );
+        assert!(plan.issue_self_signed().unwrap().is_empty());
+        std::fs::remove_dir_all(cert_dir()).unwrap();
+    } else {
+        assert!(matches!(issued, Err(VermanSchemaError::NotInstalled(_))));
+    }
+}
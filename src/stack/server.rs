@@ -0,0 +1,125 @@
+use crate::errors::VermanSchemaError;
+use crate::verman_schema::{Root, ServerConfiguration};
+
+/// Probes the servers named in `ServerConfiguration.server_priority`
+pub trait HealthCheck {
+    /// Connection details (e.g., `HOST`, `PORT`) of `server` when it is healthy, else `None`
+    fn probe(
+        &self,
+        kind: &str,
+        server: &str,
+    ) -> impl std::future::Future<
+        Output = Result<Option<indexmap::IndexMap<String, String>>, VermanSchemaError>,
+    >;
+}
+
+/// Healthy when a TCP connection to the server's address opens within `timeout`
+#[derive(Clone, Debug, PartialEq)]
+pub struct TcpHealthCheck {
+    /// Server name to `host:port`
+    pub addresses: indexmap::IndexMap<String, String>,
+    pub timeout: std::time::Duration,
+}
+
+impl HealthCheck for TcpHealthCheck {
+    async fn probe(
+        &self,
+        _kind: &str,
+        server: &str,
+    ) -> Result<Option<indexmap::IndexMap<String, String>>, VermanSchemaError> {
+        let address = match self.addresses.get(server) {
+            Some(address) => address,
+            None => return Ok(None),
+        };
+        match tokio::time::timeout(self.timeout, tokio::net::TcpStream::connect(address)).await {
+            Ok(Ok(stream)) => {
+                let peer = stream.peer_addr()?;
+                Ok(Some(indexmap::indexmap! {
+                    String::from("HOST") => peer.ip().to_string(),
+                    String::from("PORT") => peer.port().to_string(),
+                }))
+            }
+            Ok(Err(_)) | Err(_) => Ok(None),
+        }
+    }
+}
+
+/// The first healthy server of `server_priority`, in order, with `{KIND}_SERVER` and its
+/// connection details as `{KIND}_{KEY}` exported into `env_vars`. These override explicitly set
+/// `env_vars` (with a warning when they differ), as the probed server is the one in use.
+/// Errs with `NoHealthyServer` when none is healthy.
+pub async fn select_server(
+    server_configuration: &ServerConfiguration,
+    health_check: &impl HealthCheck,
+) -> Result<ServerConfiguration, VermanSchemaError> {
+    let kind = &server_configuration.kind;
+    let servers = server_configuration
+        .server_priority
+        .as_deref()
+        .unwrap_or_default();
+    for server in servers {
+        match health_check.probe(kind, server).await? {
+            Some(details) => {
+                log::info!("Selected {} for {}", server, kind);
+                let prefix = kind.to_uppercase().replace(['-', '.', ' '], "_");
+                let mut env_vars = server_configuration.env_vars.to_owned().unwrap_or_default();
+                let probed = std::iter::once((format!("{}_SERVER", prefix), server.to_owned()))
+                    .chain(
+                        details
+                            .into_iter()
+                            .map(|(k, v)| (format!("{}_{}", prefix, k), v)),
+                    );
+                for (key, value) in probed {
+                    if let Some(explicit) = env_vars.insert(key.to_owned(), value.to_owned()) {
+                        if explicit != value {
+                            log::warn!(
+                                "{} of {} is {:?} but {} answers on {:?}",
+                                key,
+                                kind,
+                                explicit,
+                                server,
+                                value
+                            );
+                        }
+                    }
+                }
+                return Ok(ServerConfiguration {
+                    env_vars: Some(env_vars),
+                    ..server_configuration.to_owned()
+                });
+            }
+            None => log::warn!("{} for {} is not healthy, moving to next", server, kind),
+        }
+    }
+    Err(VermanSchemaError::NoHealthyServer {
+        kind: kind.to_owned(),
+        tried: servers.to_vec(),
+    })
+}
+
+/// `select_server` for every `ServerConfiguration` of `root` with a `server_priority`
+pub async fn select_servers(
+    root: &Root,
+    health_check: &impl HealthCheck,
+) -> Result<Root, VermanSchemaError> {
+    let mut stack =
+        indexmap::IndexMap::<String, Vec<ServerConfiguration>>::with_capacity(root.stack.len());
+    for (name, server_configurations) in root.stack.iter() {
+        let mut selected = Vec::<ServerConfiguration>::with_capacity(server_configurations.len());
+        for server_configuration in server_configurations {
+            selected.push(match server_configuration.server_priority {
+                Some(_) => select_server(server_configuration, health_check).await?,
+                None => server_configuration.to_owned(),
+            });
+        }
+        stack.insert(name.to_owned(), selected);
+    }
+    Ok(Root {
+        stack,
+        ..root.to_owned()
+    })
+}
+
+#[cfg(test)]
+#[path = "server_test.rs"]
+mod tests;
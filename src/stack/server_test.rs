@@ -0,0 +1,91 @@
+use super::*;
+
+/// Healthy servers and their `PORT`
+struct Fake(indexmap::IndexMap<&'static str, &'static str>);
+
+impl HealthCheck for Fake {
+    async fn probe(
+        &self,
+        _kind: &str,
+        server: &str,
+    ) -> Result<Option<indexmap::IndexMap<String, String>>, VermanSchemaError> {
+        Ok(self
+            .0
+            .get(server)
+            .map(|port| indexmap::indexmap! { String::from("PORT") => port.to_string() }))
+    }
+}
+
+fn postgres(server_priority: &[&str]) -> ServerConfiguration {
+    ServerConfiguration {
+        kind: String::from("postgres"),
+        server_priority: Some(server_priority.iter().map(|s| s.to_string()).collect()),
+        env_vars: Some(indexmap::indexmap! {
+            String::from("POSTGRES_PORT") => String::from("6543"),
+        }),
+        ..ServerConfiguration::default()
+    }
+}
+
+#[tokio::test]
+async fn select_server_fallback_test() {
+    let selected = select_server(
+        &postgres(&["local", "docker", "remote"]),
+        &Fake(indexmap::indexmap! { "docker" => "5432", "remote" => "5433" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        selected.env_vars,
+        Some(indexmap::indexmap! {
+            String::from("POSTGRES_PORT") => String::from("5432"),
+            String::from("POSTGRES_SERVER") => String::from("docker"),
+        })
+    );
+}
+
+#[tokio::test]
+async fn select_server_none_healthy_test() {
+    match select_server(
+        &postgres(&["local", "docker"]),
+        &Fake(indexmap::IndexMap::new()),
+    )
+    .await
+    {
+        Err(VermanSchemaError::NoHealthyServer { kind, tried }) => {
+            assert_eq!(kind, "postgres");
+            assert_eq!(tried, vec!["local", "docker"]);
+        }
+        other => panic!("expected `NoHealthyServer`, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn tcp_health_check_test() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let health_check = TcpHealthCheck {
+        addresses: indexmap::indexmap! {
+            String::from("up") => format!("127.0.0.1:{}", port),
+        },
+        timeout: std::time::Duration::from_secs(5),
+    };
+    let root = select_servers(
+        &Root {
+            stack: indexmap::indexmap! {
+                String::from("database") => vec![ServerConfiguration {
+                    env_vars: None,
+                    ..postgres(&["down", "up"])
+                }],
+            },
+            ..Root::default()
+        },
+        &health_check,
+    )
+    .await
+    .unwrap();
+    let env_vars = root.stack["database"][0].env_vars.to_owned().unwrap();
+    assert_eq!(env_vars["POSTGRES_SERVER"], "up");
+    assert_eq!(env_vars["POSTGRES_HOST"], "127.0.0.1");
+    assert_eq!(env_vars["POSTGRES_PORT"], port.to_string());
+}
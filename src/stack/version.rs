@@ -0,0 +1,166 @@
+use crate::errors::VermanSchemaError;
+use crate::verman_schema::Root;
+
+/// Parsed `Constraint.required_version`
+#[derive(Clone, Debug, PartialEq)]
+pub enum VersionConstraint {
+    /// `latest`: any version, the resolver picking the highest
+    Latest,
+
+    /// Cargo-style requirement, e.g., `^1.2` | `~1.2.3` | `>=3,<4` | `=1.0.0`
+    Req(semver::VersionReq),
+}
+
+impl VersionConstraint {
+    pub fn matches(&self, version: &semver::Version) -> bool {
+        match self {
+            Self::Latest => true,
+            Self::Req(req) => req.matches(version),
+        }
+    }
+}
+
+impl std::str::FromStr for VersionConstraint {
+    type Err = VermanSchemaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "latest" | "" => Ok(Self::Latest),
+            s => Ok(Self::Req(semver::VersionReq::parse(s)?)),
+        }
+    }
+}
+
+impl std::fmt::Display for VersionConstraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Latest => write!(f, "latest"),
+            Self::Req(req) => write!(f, "{}", req),
+        }
+    }
+}
+
+/// Lenient version parsing for `ServerConfiguration.versions`: a leading `v` is dropped and
+/// missing minor and patch are zero, so `"16"` is `16.0.0` and `"v1.2"` is `1.2.0`
+pub fn parse_version(s: &str) -> Result<semver::Version, VermanSchemaError> {
+    let s = s.trim();
+    let s = s.strip_prefix('v').unwrap_or(s);
+    let core_len = s.find(['-', '+']).unwrap_or(s.len());
+    let missing = 2usize.saturating_sub(s[..core_len].matches('.').count());
+    let padded = format!(
+        "{}{}{}",
+        &s[..core_len],
+        ".0".repeat(missing),
+        &s[core_len..]
+    );
+    Ok(semver::Version::parse(&padded)?)
+}
+
+/// Highest version per kind among its `ServerConfiguration.versions` that satisfies every
+/// `Constraint.required_version` of that kind across `Root.component`.
+/// Kinds with neither versions nor constraints are left out.
+/// Errs with `NoCandidateVersions` when a constrained kind has no `ServerConfiguration.versions`,
+/// and with `VersionConflict` naming the constraints that can't be satisfied together.
+pub fn resolve_versions(
+    root: &Root,
+) -> Result<indexmap::IndexMap<String, semver::Version>, VermanSchemaError> {
+    let mut candidates = indexmap::IndexMap::<String, Vec<semver::Version>>::new();
+    for server_configuration in root.stack.values().flatten() {
+        let versions = candidates
+            .entry(server_configuration.kind.to_owned())
+            .or_default();
+        for version in server_configuration.versions.iter().flatten() {
+            versions.push(parse_version(version)?);
+        }
+    }
+    /* kind -> [(where, constraint)] */
+    let mut constraints = indexmap::IndexMap::<String, Vec<(String, VersionConstraint)>>::new();
+    for (i, component) in root.component.iter().enumerate() {
+        for constraint in component.constraints.iter() {
+            if let Some(required_version) = &constraint.required_version {
+                constraints
+                    .entry(constraint.kind.to_owned())
+                    .or_default()
+                    .push((format!("component[{}]", i), required_version.parse()?));
+            }
+        }
+    }
+
+    let mut resolved = indexmap::IndexMap::<String, semver::Version>::new();
+    for kind in candidates.keys().chain(constraints.keys()) {
+        if resolved.contains_key(kind) {
+            continue;
+        }
+        let versions = candidates.get(kind).map(Vec::as_slice).unwrap_or_default();
+        let kind_constraints = constraints.get(kind).map(Vec::as_slice).unwrap_or_default();
+        if versions.is_empty() {
+            if kind_constraints.is_empty() {
+                continue;
+            }
+            return Err(VermanSchemaError::NoCandidateVersions {
+                kind: kind.to_owned(),
+                constraints: kind_constraints
+                    .iter()
+                    .map(|(location, c)| format!("{}: {}", location, c))
+                    .collect(),
+            });
+        }
+        match versions
+            .iter()
+            .filter(|version| kind_constraints.iter().all(|(_, c)| c.matches(version)))
+            .max()
+        {
+            Some(version) => {
+                resolved.insert(kind.to_owned(), version.to_owned());
+            }
+            None => {
+                return Err(VermanSchemaError::VersionConflict {
+                    kind: kind.to_owned(),
+                    constraints: conflicting(versions, kind_constraints),
+                })
+            }
+        }
+    }
+    Ok(resolved)
+}
+
+/// Constraints no version satisfies on their own, else pairs that no version satisfies together,
+/// else all of them; formatted as `"component[i]: constraint"`
+fn conflicting(
+    versions: &[semver::Version],
+    constraints: &[(String, VersionConstraint)],
+) -> Vec<String> {
+    let satisfiable = |cs: &[&(String, VersionConstraint)]| {
+        versions
+            .iter()
+            .any(|version| cs.iter().all(|(_, c)| c.matches(version)))
+    };
+    let fmt = |(location, c): &(String, VersionConstraint)| format!("{}: {}", location, c);
+
+    let unsatisfiable: Vec<String> = constraints
+        .iter()
+        .filter(|c| !satisfiable(&[c]))
+        .map(fmt)
+        .collect();
+    if !unsatisfiable.is_empty() || constraints.is_empty() {
+        return unsatisfiable;
+    }
+    let mut pairs = indexmap::IndexSet::<String>::new();
+    for (i, a) in constraints.iter().enumerate() {
+        for b in constraints[i + 1..].iter() {
+            if !satisfiable(&[a, b]) {
+                pairs.insert(fmt(a));
+                pairs.insert(fmt(b));
+            }
+        }
+    }
+    if pairs.is_empty() {
+        constraints.iter().map(fmt).collect()
+    } else {
+        pairs.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+#[path = "version_test.rs"]
+mod tests;
@@ -0,0 +1,81 @@
+use super::*;
+use crate::verman_schema::{Component, Constraint, ServerConfiguration};
+
+fn root(versions: &[&str], required_versions: &[&str]) -> Root {
+    Root {
+        stack: indexmap::indexmap! {
+            String::from("database") => vec![ServerConfiguration {
+                kind: String::from("postgres"),
+                versions: Some(versions.iter().map(|v| v.to_string()).collect()),
+                ..ServerConfiguration::default()
+            }],
+        },
+        component: required_versions
+            .iter()
+            .map(|required_version| Component {
+                constraints: vec![Constraint {
+                    kind: String::from("postgres"),
+                    required_version: Some(required_version.to_string()),
+                    ..Constraint::default()
+                }],
+                ..Component::default()
+            })
+            .collect(),
+        ..Root::default()
+    }
+}
+
+#[test]
+fn version_constraint_parse_test() {
+    let v = |s: &str| parse_version(s).unwrap();
+    assert_eq!(v("16"), semver::Version::new(16, 0, 0));
+    assert_eq!(v("v1.2"), semver::Version::new(1, 2, 0));
+    assert_eq!(v("1.2-rc.1").to_string(), "1.2.0-rc.1");
+
+    let c = |s: &str| s.parse::<VersionConstraint>().unwrap();
+    assert_eq!(c("latest"), VersionConstraint::Latest);
+    assert!(c("^1.2").matches(&v("1.9.0")) && !c("^1.2").matches(&v("2.0.0")));
+    assert!(c(">=3,<4").matches(&v("3.5")) && !c(">=3,<4").matches(&v("4")));
+    assert!(c("~1.2.3").matches(&v("1.2.9")) && !c("~1.2.3").matches(&v("1.3.0")));
+    assert!("not a version".parse::<VersionConstraint>().is_err());
+}
+
+#[test]
+fn resolve_versions_test() {
+    assert_eq!(
+        resolve_versions(&root(&["14", "15.4", "16.1"], &[">=14", "<16", "latest"])).unwrap(),
+        indexmap::indexmap! { String::from("postgres") => semver::Version::new(15, 4, 0) }
+    );
+    assert_eq!(
+        resolve_versions(&root(&["14", "16"], &[])).unwrap()["postgres"],
+        semver::Version::new(16, 0, 0)
+    );
+}
+
+#[test]
+fn resolve_versions_conflict_test() {
+    match resolve_versions(&root(&["14", "15", "16"], &[">=14", "<15", "^16"])) {
+        Err(VermanSchemaError::VersionConflict { kind, constraints }) => {
+            assert_eq!(kind, "postgres");
+            assert_eq!(constraints, vec!["component[1]: <15", "component[2]: ^16"]);
+        }
+        other => panic!("expected `VersionConflict`, got {:?}", other),
+    }
+    match resolve_versions(&root(&["14"], &["^15"])) {
+        Err(VermanSchemaError::VersionConflict { constraints, .. }) => {
+            assert_eq!(constraints, vec!["component[0]: ^15"])
+        }
+        other => panic!("expected `VersionConflict`, got {:?}", other),
+    }
+}
+
+#[test]
+fn resolve_versions_no_candidates_test() {
+    match resolve_versions(&root(&[], &["^15"])) {
+        Err(VermanSchemaError::NoCandidateVersions { kind, constraints }) => {
+            assert_eq!(kind, "postgres");
+            assert_eq!(constraints, vec!["component[0]: ^15"]);
+        }
+        other => panic!("expected `NoCandidateVersions`, got {:?}", other),
+    }
+}
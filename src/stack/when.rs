@@ -0,0 +1,439 @@
+use crate::constants::predefined_constants;
+use crate::errors::VermanSchemaError;
+use crate::stack::version::parse_version;
+use crate::verman_schema::{Component, Mount};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+    Not,
+    And,
+    Or,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    In,
+    Bool(bool),
+    Str(String),
+    List(Vec<Value>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    Bool(bool),
+    Str(String),
+    List(Vec<Value>),
+}
+
+impl Value {
+    fn from_json(value: &serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Bool(b) => Self::Bool(*b),
+            serde_json::Value::String(s) => Self::Str(s.to_owned()),
+            serde_json::Value::Array(arr) => Self::List(arr.iter().map(Self::from_json).collect()),
+            serde_json::Value::Null => Self::Str(String::new()),
+            other => Self::Str(other.to_string()),
+        }
+    }
+
+    fn as_string(&self) -> String {
+        match self {
+            Self::Bool(b) => b.to_string(),
+            Self::Str(s) => s.to_owned(),
+            Self::List(values) => format!(
+                "[{}]",
+                values
+                    .iter()
+                    .map(Self::as_string)
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+struct Parser<'a> {
+    expression: &'a str,
+    vars: &'a indexmap::IndexMap<String, serde_json::Value>,
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    /// On the right of a short-circuited `&&` or `||`: parsed but not evaluated
+    skipping: bool,
+}
+
+/// Evaluate the `when` expression against `vars`, e.g., `${OS} == "windows" && ${ARCH} in ["x86_64", "aarch64"]`.
+///
+/// - `${NAME}` is the value of `NAME`; when not found `${NAME}` is left as `${NAME}`
+/// - `"…"` and `'…'` are strings, with `${NAME}` interpolated; bare words like `linux` or `1.2`
+///   are strings too, except `true` and `false`
+/// - `==` `!=` compare as strings; `<` `<=` `>` `>=` compare as versions, see
+///   `version::parse_version`
+/// - `a in [b, c]` is list membership, as is `a in ${NAME}` when `NAME` is an array;
+///   `a in "abc"` is substring
+/// - `!`, `&&`, `||` and parentheses, with the usual precedence; `&&` and `||` short-circuit, so
+///   e.g. `${V} != "" && ${V} > 1` does not err when `V` is empty
+///
+/// An empty `expression` is `true`. Nothing is executed, so expressions from untrusted files are
+/// safe to evaluate.
+pub fn evaluate(
+    expression: &str,
+    vars: &indexmap::IndexMap<String, serde_json::Value>,
+) -> Result<bool, VermanSchemaError> {
+    if expression.trim().is_empty() {
+        return Ok(true);
+    }
+    let mut parser = Parser {
+        expression,
+        vars,
+        tokens: Vec::new(),
+        pos: 0,
+        skipping: false,
+    };
+    parser.tokens = parser.tokenize()?;
+    let value = parser.or()?;
+    if parser.pos < parser.tokens.len() {
+        return Err(parser.error("unexpected token"));
+    }
+    parser.truthy(value)
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> VermanSchemaError {
+        VermanSchemaError::InvalidExpression {
+            expression: self.expression.to_owned(),
+            position: self
+                .tokens
+                .get(self.pos)
+                .map(|(position, _)| *position)
+                .unwrap_or(self.expression.len()),
+            message: message.to_owned(),
+        }
+    }
+
+    fn error_at(&self, position: usize, message: &str) -> VermanSchemaError {
+        VermanSchemaError::InvalidExpression {
+            expression: self.expression.to_owned(),
+            position,
+            message: message.to_owned(),
+        }
+    }
+
+    fn var(&self, name: &str) -> Option<Value> {
+        self.vars.get(name).map(Value::from_json)
+    }
+
+    /// `${NAME}`s of `s` replaced by their values
+    fn interpolate(&self, s: &str) -> String {
+        let mut out = String::with_capacity(s.len());
+        let mut rest = s;
+        while let Some(start) = rest.find("${") {
+            out.push_str(&rest[..start]);
+            match rest[start..].find('}') {
+                Some(end) => {
+                    let reference = &rest[start..start + end + 1];
+                    match self.var(&reference[2..reference.len() - 1]) {
+                        Some(value) => out.push_str(&value.as_string()),
+                        None => out.push_str(reference),
+                    }
+                    rest = &rest[start + end + 1..];
+                }
+                None => {
+                    out.push_str(&rest[start..]);
+                    rest = "";
+                }
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+
+    fn tokenize(&self) -> Result<Vec<(usize, Token)>, VermanSchemaError> {
+        let mut tokens = Vec::<(usize, Token)>::new();
+        let chars: Vec<(usize, char)> = self.expression.char_indices().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            let (position, c) = chars[i];
+            let next = chars.get(i + 1).map(|(_, c)| *c);
+            let (token, len) = match (c, next) {
+                (c, _) if c.is_whitespace() => {
+                    i += 1;
+                    continue;
+                }
+                ('(', _) => (Token::LParen, 1),
+                (')', _) => (Token::RParen, 1),
+                ('[', _) => (Token::LBracket, 1),
+                (']', _) => (Token::RBracket, 1),
+                (',', _) => (Token::Comma, 1),
+                ('&', Some('&')) => (Token::And, 2),
+                ('|', Some('|')) => (Token::Or, 2),
+                ('=', Some('=')) => (Token::Eq, 2),
+                ('!', Some('=')) => (Token::Ne, 2),
+                ('!', _) => (Token::Not, 1),
+                ('<', Some('=')) => (Token::Le, 2),
+                ('<', _) => (Token::Lt, 1),
+                ('>', Some('=')) => (Token::Ge, 2),
+                ('>', _) => (Token::Gt, 1),
+                ('"' | '\'', _) => {
+                    let mut s = String::new();
+                    let mut j = i + 1;
+                    loop {
+                        match chars.get(j) {
+                            None => return Err(self.error_at(position, "unterminated string")),
+                            Some((_, '\\')) => {
+                                if let Some((_, escaped)) = chars.get(j + 1) {
+                                    s.push(*escaped);
+                                }
+                                j += 2;
+                            }
+                            Some((_, quote)) if *quote == c => break,
+                            Some((_, other)) => {
+                                s.push(*other);
+                                j += 1;
+                            }
+                        }
+                    }
+                    (Token::Str(self.interpolate(&s)), j + 1 - i)
+                }
+                ('$', Some('{')) => {
+                    let end = chars[i..]
+                        .iter()
+                        .position(|(_, c)| *c == '}')
+                        .ok_or_else(|| self.error_at(position, "unterminated `${`"))?;
+                    let reference: String = chars[i..i + end + 1].iter().map(|(_, c)| c).collect();
+                    let token = match self.var(&reference[2..reference.len() - 1]) {
+                        Some(Value::Bool(b)) => Token::Bool(b),
+                        Some(Value::List(values)) => Token::List(values),
+                        Some(value) => Token::Str(value.as_string()),
+                        None => Token::Str(reference),
+                    };
+                    (token, end + 1)
+                }
+                (c, _) if is_word_char(c) => {
+                    let len = chars[i..]
+                        .iter()
+                        .take_while(|(_, c)| is_word_char(*c))
+                        .count();
+                    let word: String = chars[i..i + len].iter().map(|(_, c)| c).collect();
+                    let token = match word.as_str() {
+                        "in" => Token::In,
+                        "true" => Token::Bool(true),
+                        "false" => Token::Bool(false),
+                        _ => Token::Str(word),
+                    };
+                    (token, len)
+                }
+                _ => return Err(self.error_at(position, "unexpected character")),
+            };
+            tokens.push((position, token));
+            i += len;
+        }
+        Ok(tokens)
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    fn expect(&mut self, token: Token, message: &str) -> Result<(), VermanSchemaError> {
+        if self.peek() == Some(&token) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(message))
+        }
+    }
+
+    fn truthy(&self, value: Value) -> Result<bool, VermanSchemaError> {
+        match value {
+            _ if self.skipping => Ok(false),
+            Value::Bool(b) => Ok(b),
+            Value::Str(s) if s == "true" => Ok(true),
+            Value::Str(s) if s == "false" => Ok(false),
+            _ => Err(self.error("expected a boolean")),
+        }
+    }
+
+    fn or(&mut self) -> Result<Value, VermanSchemaError> {
+        let mut value = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            let lhs = self.truthy(value)?;
+            let skipping = self.skipping;
+            self.skipping |= lhs;
+            let rhs = self.and();
+            self.skipping = skipping;
+            value = Value::Bool(lhs || self.truthy(rhs?)?);
+        }
+        Ok(value)
+    }
+
+    fn and(&mut self) -> Result<Value, VermanSchemaError> {
+        let mut value = self.unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            let lhs = self.truthy(value)?;
+            let skipping = self.skipping;
+            self.skipping |= !lhs;
+            let rhs = self.unary();
+            self.skipping = skipping;
+            value = Value::Bool(lhs && self.truthy(rhs?)?);
+        }
+        Ok(value)
+    }
+
+    fn unary(&mut self) -> Result<Value, VermanSchemaError> {
+        if self.peek() == Some(&Token::Not) {
+            self.pos += 1;
+            let value = self.unary()?;
+            return Ok(Value::Bool(!self.truthy(value)?));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Value, VermanSchemaError> {
+        let lhs = self.operand()?;
+        let op = match self.peek() {
+            Some(
+                op @ (Token::Eq
+                | Token::Ne
+                | Token::Lt
+                | Token::Le
+                | Token::Gt
+                | Token::Ge
+                | Token::In),
+            ) => op.to_owned(),
+            _ => return Ok(lhs),
+        };
+        let op_pos = self.pos;
+        self.pos += 1;
+        let rhs = self.operand()?;
+        if self.skipping {
+            return Ok(Value::Bool(false));
+        }
+        let result = match op {
+            Token::Eq => lhs.as_string() == rhs.as_string(),
+            Token::Ne => lhs.as_string() != rhs.as_string(),
+            Token::In => match rhs {
+                Value::List(values) => values.iter().any(|v| v.as_string() == lhs.as_string()),
+                other => other.as_string().contains(&lhs.as_string()),
+            },
+            _ => {
+                let version = |value: &Value| {
+                    parse_version(&value.as_string()).map_err(|_| {
+                        VermanSchemaError::InvalidExpression {
+                            expression: self.expression.to_owned(),
+                            position: self.tokens[op_pos].0,
+                            message: format!("{:?} is not a version", value.as_string()),
+                        }
+                    })
+                };
+                let ordering = version(&lhs)?.cmp(&version(&rhs)?);
+                match op {
+                    Token::Lt => ordering.is_lt(),
+                    Token::Le => ordering.is_le(),
+                    Token::Gt => ordering.is_gt(),
+                    _ => ordering.is_ge(),
+                }
+            }
+        };
+        Ok(Value::Bool(result))
+    }
+
+    fn operand(&mut self) -> Result<Value, VermanSchemaError> {
+        match self.peek().cloned() {
+            Some(Token::LParen) => {
+                self.pos += 1;
+                let value = self.or()?;
+                self.expect(Token::RParen, "expected `)`")?;
+                Ok(value)
+            }
+            Some(Token::LBracket) => {
+                self.pos += 1;
+                let mut values = Vec::<Value>::new();
+                while self.peek() != Some(&Token::RBracket) {
+                    values.push(self.operand()?);
+                    if self.peek() != Some(&Token::Comma) {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                self.expect(Token::RBracket, "expected `]`")?;
+                Ok(Value::List(values))
+            }
+            Some(Token::Str(s)) => {
+                self.pos += 1;
+                Ok(Value::Str(s))
+            }
+            Some(Token::Bool(b)) => {
+                self.pos += 1;
+                Ok(Value::Bool(b))
+            }
+            Some(Token::List(values)) => {
+                self.pos += 1;
+                Ok(Value::List(values))
+            }
+            _ => Err(self.error("expected a value")),
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '+' | ':' | '/' | '*' | '~' | '^')
+}
+
+impl Mount {
+    /// Whether `when` holds for `vars`
+    pub fn applies(
+        &self,
+        vars: &indexmap::IndexMap<String, serde_json::Value>,
+    ) -> Result<bool, VermanSchemaError> {
+        evaluate(&self.when, vars)
+    }
+
+    /// Whether `when` holds on this host, i.e., with the predefined constants
+    pub fn applies_on_host(&self) -> Result<bool, VermanSchemaError> {
+        self.applies(&predefined_constants())
+    }
+}
+
+impl Component {
+    /// `mounts` whose `when` holds for `vars`
+    pub fn applicable_mounts(
+        &self,
+        vars: &indexmap::IndexMap<String, serde_json::Value>,
+    ) -> Result<Vec<&Mount>, VermanSchemaError> {
+        let mut mounts = Vec::<&Mount>::new();
+        for mount in self.mounts.iter().flatten() {
+            if mount.applies(vars)? {
+                mounts.push(mount);
+            }
+        }
+        Ok(mounts)
+    }
+
+    /// A `Component` applies when it has no `mounts` or when one of them applies
+    pub fn applies(
+        &self,
+        vars: &indexmap::IndexMap<String, serde_json::Value>,
+    ) -> Result<bool, VermanSchemaError> {
+        match &self.mounts {
+            Some(mounts) if !mounts.is_empty() => Ok(!self.applicable_mounts(vars)?.is_empty()),
+            _ => Ok(true),
+        }
+    }
+
+    pub fn applies_on_host(&self) -> Result<bool, VermanSchemaError> {
+        self.applies(&predefined_constants())
+    }
+}
+
+#[cfg(test)]
+#[path = "when_test.rs"]
+mod tests;
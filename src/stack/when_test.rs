@@ -0,0 +1,93 @@
+use super::*;
+
+fn vars() -> indexmap::IndexMap<String, serde_json::Value> {
+    indexmap::indexmap! {
+        String::from("OS") => serde_json::json!("linux"),
+        String::from("ARCH") => serde_json::json!("x86_64"),
+        String::from("PG_VERSION") => serde_json::json!("15.4"),
+        String::from("SUPPORTED") => serde_json::json!(["linux", "macos"]),
+        String::from("CI") => serde_json::json!(true),
+    }
+}
+
+#[test]
+fn evaluate_test() {
+    for (expression, expected) in [
+        ("", true),
+        (r#"${OS} == "windows""#, false),
+        (r#"${OS} != "windows""#, true),
+        (
+            r#"${OS} == linux && ${ARCH} in ["x86_64", "aarch64"]"#,
+            true,
+        ),
+        (r#"${OS} in ${SUPPORTED}"#, true),
+        (r#""mac" in ${SUPPORTED}"#, false),
+        (r#"'86' in ${ARCH}"#, true),
+        (r#"!(${OS} == "linux") || ${CI}"#, true),
+        (r#"!${CI}"#, false),
+        (r#"${PG_VERSION} >= 15 && ${PG_VERSION} < "15.10""#, true),
+        (r#"${PG_VERSION} > 16"#, false),
+        (r#""${OS}-${ARCH}" == "linux-x86_64""#, true),
+        (r#"${MISSING} == "${MISSING}""#, true),
+        ("true && false || true", true),
+        (r#"true || ${OS} > 1"#, true),
+        (r#"false && "not a boolean""#, false),
+        (r#"false && (${OS} > 1 || "x") || ${CI}"#, true),
+    ] {
+        assert_eq!(
+            evaluate(expression, &vars()).unwrap(),
+            expected,
+            "{}",
+            expression
+        );
+    }
+}
+
+#[test]
+fn evaluate_invalid_test() {
+    for (expression, position) in [
+        (r#"${OS} == "linux"#, 9),
+        (r#"${OS} =="#, 8),
+        (r#"${OS}"#, 5),
+        (r#"(${CI}"#, 6),
+        (r#"${OS} > 1"#, 6),
+        (r#"${OS} ; true"#, 6),
+    ] {
+        match evaluate(expression, &vars()) {
+            Err(VermanSchemaError::InvalidExpression { position: p, .. }) => {
+                assert_eq!(p, position, "{}", expression)
+            }
+            other => panic!(
+                "expected `InvalidExpression` for {}, got {:?}",
+                expression, other
+            ),
+        }
+    }
+}
+
+#[test]
+fn component_applies_test() {
+    let mount = |when: &str| Mount {
+        when: when.to_string(),
+        ..Mount::default()
+    };
+    let component = Component {
+        mounts: Some(vec![
+            mount(r#"${OS} == "windows""#),
+            mount(r#"${OS} == "linux""#),
+        ]),
+        ..Component::default()
+    };
+    assert_eq!(component.applicable_mounts(&vars()).unwrap().len(), 1);
+    assert!(component.applies(&vars()).unwrap());
+    assert!(!Component {
+        mounts: Some(vec![mount(r#"${OS} == "windows""#)]),
+        ..Component::default()
+    }
+    .applies(&vars())
+    .unwrap());
+    assert!(Component::default().applies(&vars()).unwrap());
+    assert!(mount(&format!("${{OS}} == {:?}", std::env::consts::OS))
+        .applies_on_host()
+        .unwrap());
+}
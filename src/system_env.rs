@@ -0,0 +1,41 @@
+use crate::models::InheritEnv;
+
+impl InheritEnv {
+    /// Filtered process environment, sorted by name; non UTF-8 variables are skipped
+    pub fn inherited_env(&self) -> indexmap::IndexMap<String, serde_json::Value> {
+        self.filter(process_vars())
+    }
+
+    pub fn filter(
+        &self,
+        vars: impl Iterator<Item = (String, String)>,
+    ) -> indexmap::IndexMap<String, serde_json::Value> {
+        let mut env: indexmap::IndexMap<String, serde_json::Value> = vars
+            .filter(|(k, _)| self.is_inherited(k))
+            .map(|(k, v)| (k, serde_json::Value::String(v)))
+            .collect();
+        env.sort_keys();
+        env
+    }
+
+    pub fn is_inherited(&self, name: &str) -> bool {
+        if self.deny.iter().flatten().any(|denied| denied == name) {
+            return false;
+        }
+        match (&self.allow, &self.prefixes) {
+            (None, None) => true,
+            (allow, prefixes) => {
+                allow.iter().flatten().any(|allowed| allowed == name)
+                    || prefixes
+                        .iter()
+                        .flatten()
+                        .any(|prefix| name.starts_with(prefix.as_str()))
+            }
+        }
+    }
+}
+
+/// Process environment, skipping non UTF-8 variables (where `std::env::vars` would panic)
+pub(crate) fn process_vars() -> impl Iterator<Item = (String, String)> {
+    std::env::vars_os().filter_map(|(k, v)| Some((k.into_string().ok()?, v.into_string().ok()?)))
+}
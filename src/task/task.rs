@@ -1,7 +1,9 @@
 use crate::commands::command::{Command, CommandKey};
 use crate::commands::shared::merge_env;
 use crate::errors::VermanSchemaError;
-use crate::models::{CommonContent, Task};
+use crate::models::{CommonContent, Retry, Task};
+use crate::pipeline::pipeline::ProcessOptions;
+use crate::schema_validation::validate_json_schema;
 
 #[derive(derive_more::Display)]
 pub enum TaskKey {
@@ -16,44 +18,29 @@ pub async fn process_tasks_serially(
     pipeline_name: &String,
     tasks: &indexmap::IndexMap<String, Task>,
 ) -> Result<CommonContent, VermanSchemaError> {
-    let mut shared_env_for_tasks = indexmap::IndexMap::<String, serde_json::Value>::new();
+    process_tasks_serially_with_env(
+        pipeline_name,
+        tasks,
+        indexmap::IndexMap::<String, serde_json::Value>::new(),
+        &ProcessOptions::default(),
+    )
+    .await
+}
 
-    let mut i = 0usize;
-    for (task_name, task) in tasks.iter() {
+/// Like `process_tasks_serially` but starting from an existing env, e.g., the one left behind
+/// by a previous `Stage`
+pub async fn process_tasks_serially_with_env(
+    pipeline_name: &String,
+    tasks: &indexmap::IndexMap<String, Task>,
+    mut shared_env_for_tasks: indexmap::IndexMap<String, serde_json::Value>,
+    options: &ProcessOptions,
+) -> Result<CommonContent, VermanSchemaError> {
+    for (i, (task_name, task)) in tasks.iter().enumerate() {
         log::info!("Executing task {:#?}", task_name);
-        shared_env_for_tasks.insert(
-            TaskKey::CurrentName.to_string(),
-            task_name.to_string().into(),
-        );
-        let common = Task {
-            commands: task.commands.to_owned(),
-            input_schema: task.input_schema.to_owned(),
-            output_schema: task.output_schema.to_owned(),
-            env: {
-                merge_env(&mut shared_env_for_tasks, &task.env);
-                Some(shared_env_for_tasks.clone())
-            },
-        }
-        .process(pipeline_name, task_name, i)
-        .await?;
-        shared_env_for_tasks.swap_remove(TaskKey::CurrentName.to_string().as_str());
-        shared_env_for_tasks.swap_remove(CommandKey::CurrentContent.to_string().as_str()); // `None` if key not found
-                                                                                           // for security/sanity purposes, consider filtering env and only merge in necessary vars:
-        if let Some(env) = common.env {
-            shared_env_for_tasks.extend(env.to_owned());
-        }
-        shared_env_for_tasks.insert(
-            TaskKey::PreviousName.to_string(),
-            task_name.to_string().into(),
-        );
-        /*
-        alternatively could add a `Vec<u8>` or `bytes` or `impl std::io::Read` field to
-          the `CommonContent` struct
-        */
-        if let Some(value) = common.content.as_ref() {
-            shared_env_for_tasks.insert(CommandKey::PreviousContent.to_string(), value.to_owned());
-        }
-        i += 1;
+        let common = prepare_task(task_name, task, &mut shared_env_for_tasks)
+            .process(pipeline_name, task_name, i, options)
+            .await?;
+        merge_task_result(task_name, common, &mut shared_env_for_tasks);
     }
     Ok(CommonContent {
         env: Some(shared_env_for_tasks.to_owned()),
@@ -61,35 +48,276 @@ pub async fn process_tasks_serially(
     })
 }
 
+/// Run every `Task` at once, each seeing the same `shared_env_for_tasks`.
+/// Results are merged back in declaration order, so the resulting env is the same as
+/// `process_tasks_serially_with_env` would give for tasks that do not read each other's output.
+pub async fn process_tasks_concurrently_with_env(
+    pipeline_name: &String,
+    tasks: &indexmap::IndexMap<String, Task>,
+    mut shared_env_for_tasks: indexmap::IndexMap<String, serde_json::Value>,
+    options: &ProcessOptions,
+) -> Result<CommonContent, VermanSchemaError> {
+    let prepared_tasks: Vec<Task> = tasks
+        .iter()
+        .map(|(task_name, task)| {
+            log::info!("Executing task {:#?}", task_name);
+            prepare_task(task_name, task, &mut shared_env_for_tasks.clone())
+        })
+        .collect();
+    let results = futures::future::try_join_all(
+        prepared_tasks
+            .iter()
+            .zip(tasks.keys())
+            .enumerate()
+            .map(|(i, (task, task_name))| task.process(pipeline_name, task_name, i, options)),
+    )
+    .await?;
+    for ((task_name, task), common) in tasks.iter().zip(results) {
+        merge_env(&mut shared_env_for_tasks, &task.env);
+        merge_task_result(task_name, common, &mut shared_env_for_tasks);
+    }
+    Ok(CommonContent {
+        env: Some(shared_env_for_tasks.to_owned()),
+        ..CommonContent::default()
+    })
+}
+
+/// Merge `task.env` into the shared env and give back the `Task` ready to be processed
+pub(crate) fn prepare_task(
+    task_name: &String,
+    task: &Task,
+    shared_env_for_tasks: &mut indexmap::IndexMap<String, serde_json::Value>,
+) -> Task {
+    shared_env_for_tasks.insert(
+        TaskKey::CurrentName.to_string(),
+        task_name.to_string().into(),
+    );
+    Task {
+        env: {
+            merge_env(shared_env_for_tasks, &task.env);
+            Some(shared_env_for_tasks.clone())
+        },
+        ..task.to_owned()
+    }
+}
+
+/// Fold the result of a processed `Task` into the env shared with the next tasks
+pub(crate) fn merge_task_result(
+    task_name: &String,
+    common: CommonContent,
+    shared_env_for_tasks: &mut indexmap::IndexMap<String, serde_json::Value>,
+) {
+    // for security/sanity purposes, consider filtering env and only merge in necessary vars:
+    if let Some(env) = common.env {
+        shared_env_for_tasks.extend(env.to_owned());
+    }
+    shared_env_for_tasks.swap_remove(TaskKey::CurrentName.to_string().as_str());
+    shared_env_for_tasks.swap_remove(CommandKey::CurrentContent.to_string().as_str()); // `None` if key not found
+    shared_env_for_tasks.insert(
+        TaskKey::PreviousName.to_string(),
+        task_name.to_string().into(),
+    );
+    /*
+    alternatively could add a `Vec<u8>` or `bytes` or `impl std::io::Read` field to
+      the `CommonContent` struct
+    */
+    if let Some(value) = common.content.as_ref() {
+        shared_env_for_tasks.insert(CommandKey::PreviousContent.to_string(), value.to_owned());
+    }
+}
+
+/// How a `Command` of a `Task` runs, see `Task::command_policy`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CommandPolicy {
+    pub retry: Option<Retry>,
+    pub timeout_ms: Option<u64>,
+    pub continue_on_error: bool,
+}
+
 impl Task {
-    async fn process(
+    /// Run its `Command`s in order, within `timeout_ms` altogether (retries included)
+    pub(crate) async fn process(
+        &self,
+        pipeline_name: &String,
+        task_name: &String,
+        idx: usize,
+        options: &ProcessOptions,
+    ) -> Result<CommonContent, VermanSchemaError> {
+        match self.timeout_ms {
+            Some(timeout_ms) => tokio::time::timeout(
+                std::time::Duration::from_millis(timeout_ms),
+                self.process_commands(pipeline_name, task_name, idx, options),
+            )
+            .await
+            .unwrap_or(Err(VermanSchemaError::Timeout(timeout_ms))),
+            None => {
+                self.process_commands(pipeline_name, task_name, idx, options)
+                    .await
+            }
+        }
+    }
+
+    async fn process_commands(
         &self,
         pipeline_name: &String,
         task_name: &String,
         mut idx: usize,
+        options: &ProcessOptions,
     ) -> Result<CommonContent, VermanSchemaError> {
         let mut shared_env_for_cmds = match &self.env {
             Some(e) => e.to_owned(),
             None => indexmap::IndexMap::<String, serde_json::Value>::new(),
         };
+        if let Some(ref input_schema) = self.input_schema {
+            validate_json_schema(
+                input_schema,
+                shared_env_for_cmds
+                    .get(CommandKey::PreviousContent.to_string().as_str())
+                    .unwrap_or(&serde_json::Value::Null),
+                format!("{}.input_schema", task_name),
+            )?;
+        }
         let mut last_result: Result<CommonContent, VermanSchemaError> =
             Err(VermanSchemaError::NotFound("`Command`s"));
         for command in &self.commands {
-            last_result = Ok(command.process(&mut shared_env_for_cmds).await?);
-            /* fail at first failing task, without retries or force continuing */
+            let policy = self.command_policy(command);
+            match process_command(command, &mut shared_env_for_cmds, options, &policy).await {
+                Ok(common) => {
+                    last_result = Ok(common);
 
-            // cache results
-            Command::cache(
-                pipeline_name,
-                task_name,
-                idx,
-                &mut shared_env_for_cmds,
-                &mut last_result,
-            )?;
+                    // cache results
+                    Command::cache(
+                        pipeline_name,
+                        task_name,
+                        idx,
+                        &mut shared_env_for_cmds,
+                        &mut last_result,
+                    )?;
+                    /* only the error of the `Command` right before, if it failed */
+                    shared_env_for_cmds.swap_remove(CommandKey::LastError.to_string().as_str());
+                }
+                Err(error) if policy.continue_on_error => {
+                    log::warn!("Continuing task {:?} after {}", task_name, error);
+                    shared_env_for_cmds.insert(
+                        CommandKey::LastError.to_string(),
+                        serde_json::Value::String(error.to_string()),
+                    );
+                    if last_result.is_err() {
+                        last_result = Ok(CommonContent::default());
+                    }
+                }
+                Err(error) => return Err(error),
+            }
             idx += 1;
         }
-        last_result
+        if let (Some(ref output_schema), Ok(common), false) =
+            (&self.output_schema, &last_result, options.dry_run)
+        {
+            /* validated as cached, i.e., with JSON strings already parsed; a dry run's content is
+            a report of what would have run */
+            validate_json_schema(
+                output_schema,
+                &common
+                    .content
+                    .as_ref()
+                    .map_or(serde_json::Value::Null, Command::parsed_content),
+                format!("{}.output_schema", task_name),
+            )?;
+        }
+        /* hand back the cached `*_CMD_CONTENT` keys so that later tasks can reference them */
+        last_result.map(|common| CommonContent {
+            env: Some(shared_env_for_cmds),
+            ..common
+        })
     }
+
+    /// `retry`, `timeout_ms` and `continue_on_error` of `command`'s `CommonContent`; `retry` and
+    /// `continue_on_error` fall back to this `Task`'s, whose `timeout_ms` bounds the whole `Task`
+    /// instead
+    pub fn command_policy(&self, command: &Command) -> CommandPolicy {
+        let common_content = command.common_content();
+        CommandPolicy {
+            retry: common_content
+                .retry
+                .to_owned()
+                .or_else(|| self.retry.to_owned()),
+            timeout_ms: common_content.timeout_ms,
+            continue_on_error: common_content
+                .continue_on_error
+                .or(self.continue_on_error)
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// `Command::process_with`, limited to `policy.timeout_ms` per attempt and retried following
+/// `policy.retry`
+async fn process_command(
+    command: &Command,
+    shared_env_for_cmds: &mut indexmap::IndexMap<String, serde_json::Value>,
+    options: &ProcessOptions,
+    policy: &CommandPolicy,
+) -> Result<CommonContent, VermanSchemaError> {
+    let mut attempt = 0;
+    let mut backoff_ms = policy.retry.as_ref().map_or(0, |retry| retry.backoff_ms);
+    loop {
+        let result = match policy.timeout_ms {
+            Some(timeout_ms) if command.is_async() => tokio::time::timeout(
+                std::time::Duration::from_millis(timeout_ms),
+                command.process_with(shared_env_for_cmds, options),
+            )
+            .await
+            .unwrap_or(Err(VermanSchemaError::Timeout(timeout_ms))),
+            Some(timeout_ms) => tokio::time::timeout(
+                std::time::Duration::from_millis(timeout_ms),
+                process_on_thread(command, shared_env_for_cmds, options),
+            )
+            .await
+            .unwrap_or(Err(VermanSchemaError::Timeout(timeout_ms))),
+            None => command.process_with(shared_env_for_cmds, options).await,
+        };
+        match (result, &policy.retry) {
+            (Err(error), Some(retry)) if attempt < retry.count && retry.should_retry(&error) => {
+                attempt += 1;
+                log::warn!(
+                    "Retrying ({}/{}) in {}ms after {}",
+                    attempt,
+                    retry.count,
+                    backoff_ms,
+                    error
+                );
+                tokio::time::sleep(std::time::Duration::from_millis(backoff_ms)).await;
+                backoff_ms = backoff_ms.saturating_mul(2);
+            }
+            (result, _) => return result,
+        }
+    }
+}
+
+/// `Command::process_with` of a synchronous `Command` on its own thread, so that the caller can
+/// stop waiting for it.
+/// A timed out thread still runs to completion, within the `Command`'s own limits (e.g.,
+/// `commands::js::TIMEOUT`, `commands::lua::TIMEOUT` or the fuel of `Command::Wasm`).
+/// Errors (some of which can't be sent across threads) come back as `VermanSchemaError::Task`.
+async fn process_on_thread(
+    command: &Command,
+    shared_env_for_cmds: &mut indexmap::IndexMap<String, serde_json::Value>,
+    options: &ProcessOptions,
+) -> Result<CommonContent, VermanSchemaError> {
+    let (sender, receiver) = futures::channel::oneshot::channel();
+    let command = command.to_owned();
+    let mut env = shared_env_for_cmds.to_owned();
+    let options = options.to_owned();
+    std::thread::spawn(move || {
+        let result = futures::executor::block_on(command.process_with(&mut env, &options))
+            .map_err(|error| error.to_string());
+        let _ = sender.send((result, env));
+    });
+    let (result, env) = receiver
+        .await
+        .map_err(|_| VermanSchemaError::Task(String::from("`Command` thread panicked")))?;
+    *shared_env_for_cmds = env;
+    result.map_err(VermanSchemaError::Task)
 }
 
 #[cfg(test)]
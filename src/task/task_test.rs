@@ -1,5 +1,7 @@
-use crate::commands::command::Command;
-use crate::models::{CommonContent, Task};
+use crate::commands::command::{Command, CommandKey};
+use crate::errors::{SchemaViolation, VermanSchemaError};
+use crate::models::{CommonContent, ExecArgs, ExecCommandArgs, Retry, Task};
+use crate::pipeline::pipeline::ProcessOptions;
 
 #[test]
 fn test_task_string_env_value() {
@@ -9,6 +11,7 @@ fn test_task_string_env_value() {
             env: Some(indexmap::indexmap! {
                 String::from("Foo") => serde_json::Value::String(String::from("Bar"))
             }),
+            ..CommonContent::default()
         })],
         ..Task::default()
     };
@@ -29,6 +32,7 @@ fn test_task_json_env_value() {
             env: Some(indexmap::indexmap! {
                 String::from("weird") => serde_json::Value::String(String::from("[{\"cmd\":\"Echo\",\"content\":\"${math_messages}\",\"env\":{\"Foo\":\"Bar\"}}]"))
             }),
+            ..CommonContent::default()
         })],
         ..Task::default()
     };
@@ -49,11 +53,303 @@ fn test_task_serialise_into_env_from_json_value() {
             env: Some(indexmap::indexmap! {
                 String::from("weird") => serde_json::Value::String(String::new())
             }),
+            ..CommonContent::default()
         })],
         ..Task::default()
     };
 }
 
+#[tokio::test]
+async fn test_task_output_schema_violations() {
+    let task3 = Task {
+        commands: vec![Command::Echo(CommonContent {
+            content: Some(serde_json::Value::String(String::from(
+                "{\"a\": \"x\", \"b\": [1, \"y\"]}",
+            ))),
+            env: None,
+            ..CommonContent::default()
+        })],
+        output_schema: Some(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "a": {"type": "integer"},
+                "b": {"type": "array", "items": {"type": "integer"}}
+            }
+        })),
+        ..Task::default()
+    };
+    match task3
+        .process(
+            &String::from("pipeline"),
+            &String::from("task3"),
+            0,
+            &ProcessOptions::default(),
+        )
+        .await
+    {
+        Err(VermanSchemaError::SchemaValidation {
+            location,
+            violations,
+        }) => {
+            assert_eq!(location, "task3.output_schema");
+            assert_eq!(
+                violations
+                    .iter()
+                    .map(|SchemaViolation { instance_path, .. }| instance_path.as_str())
+                    .collect::<Vec<&str>>(),
+                vec!["/a", "/b/1"]
+            );
+        }
+        other => panic!("expected `SchemaValidation` got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_task_output_schema_checks_last_content() {
+    /* `CMD_PREVIOUS_CONTENT` satisfies the schema but the last `Command` has no content */
+    let task = Task {
+        commands: vec![Command::SetEnv(CommonContent {
+            content: None,
+            env: Some(indexmap::indexmap! {
+                String::from("X") => serde_json::Value::String(String::from("y"))
+            }),
+            ..CommonContent::default()
+        })],
+        output_schema: Some(serde_json::json!({"type": "object"})),
+        env: Some(indexmap::indexmap! {
+            CommandKey::PreviousContent.to_string() => serde_json::json!({"a": 1})
+        }),
+        ..Task::default()
+    };
+    assert!(matches!(
+        task.process(
+            &String::from("pipeline"),
+            &String::from("task"),
+            0,
+            &ProcessOptions::default(),
+        )
+        .await,
+        Err(VermanSchemaError::SchemaValidation { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_task_input_schema() {
+    let input_schema = serde_json::json!({"type": "object", "required": ["message"]});
+    let task4 = |previous_content: serde_json::Value| Task {
+        commands: vec![Command::Echo(CommonContent {
+            content: Some(serde_json::Value::String(String::from("${CMD_PREVIOUS_CONTENT}"))),
+            env: None,
+            ..CommonContent::default()
+        })],
+        input_schema: Some(input_schema.clone()),
+        env: Some(indexmap::indexmap! {
+            CommandKey::PreviousContent.to_string() => previous_content
+        }),
+        ..Task::default()
+    };
+    assert!(task4(serde_json::json!({"message": "hi"}))
+        .process(
+            &String::from("pipeline"),
+            &String::from("task4"),
+            0,
+            &ProcessOptions::default(),
+        )
+        .await
+        .is_ok());
+    match task4(serde_json::json!({"msg": "hi"}))
+        .process(
+            &String::from("pipeline"),
+            &String::from("task4"),
+            0,
+            &ProcessOptions::default(),
+        )
+        .await
+    {
+        Err(VermanSchemaError::SchemaValidation { violations, .. }) => {
+            assert_eq!(violations.len(), 1);
+            assert_eq!(violations[0].instance_path, "");
+        }
+        other => panic!("expected `SchemaValidation` got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_task_continue_on_error() {
+    let failing = |continue_on_error: Option<bool>| {
+        Command::Jaq(CommonContent {
+            content: Some(serde_json::Value::String(String::from(".[0] |"))),
+            env: Some(indexmap::indexmap! {
+                CommandKey::PreviousContent.to_string() => serde_json::json!([1])
+            }),
+            continue_on_error,
+            ..CommonContent::default()
+        })
+    };
+    let task5 = Task {
+        commands: vec![
+            failing(Some(true)),
+            Command::Interpolate(CommonContent {
+                content: Some(serde_json::Value::String(String::from("${CMD_LAST_ERROR}"))),
+                ..CommonContent::default()
+            }),
+            Command::Echo(CommonContent {
+                content: Some(serde_json::Value::String(String::from("after"))),
+                ..CommonContent::default()
+            }),
+        ],
+        ..Task::default()
+    };
+    let common = task5
+        .process(
+            &String::from("pipeline"),
+            &String::from("task5"),
+            0,
+            &ProcessOptions::default(),
+        )
+        .await
+        .unwrap();
+    let env = common.env.unwrap();
+    assert!(!env.contains_key("pipeline__task5[0]_CMD_CONTENT"));
+    /* seen by the next `Command`, then cleared once it succeeded */
+    assert!(matches!(
+        env.get("pipeline__task5[1]_CMD_CONTENT"),
+        Some(serde_json::Value::String(error)) if !error.is_empty()
+    ));
+    assert!(!env.contains_key(CommandKey::LastError.to_string().as_str()));
+    assert_eq!(
+        env.get("pipeline__task5[2]_CMD_CONTENT").unwrap(),
+        &serde_json::Value::String(String::from("after"))
+    );
+
+    /* the `Command`'s own `continue_on_error` wins over the `Task`'s */
+    let task6 = Task {
+        commands: vec![failing(Some(false))],
+        continue_on_error: Some(true),
+        ..Task::default()
+    };
+    assert!(task6
+        .process(
+            &String::from("pipeline"),
+            &String::from("task6"),
+            0,
+            &ProcessOptions::default(),
+        )
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_task_retry_and_timeout() {
+    let counter = std::env::temp_dir().join(format!("verman_retry_{}", std::process::id()));
+    let _ = std::fs::remove_file(&counter);
+    /* fails on the first two attempts */
+    let flaky = |retry: Option<Retry>| {
+        Command::Exec(ExecCommandArgs {
+            args: ExecArgs {
+                program: String::from("/bin/sh"),
+                args: vec![
+                    String::from("-c"),
+                    format!(
+                        "n=$(($(cat {0} 2>/dev/null || echo 0) + 1)); echo $n > {0}; [ $n -ge 3 ]",
+                        counter.display()
+                    ),
+                ],
+                cwd: None,
+            },
+            common_content: CommonContent {
+                retry,
+                ..CommonContent::default()
+            },
+            ..ExecCommandArgs::default()
+        })
+    };
+    let task7 = Task {
+        commands: vec![flaky(None)],
+        retry: Some(Retry {
+            count: 1,
+            backoff_ms: 1,
+            retry_on: None,
+        }),
+        ..Task::default()
+    };
+    let process = |task: Task| async move {
+        task.process(
+            &String::from("pipeline"),
+            &String::from("task7"),
+            0,
+            &ProcessOptions::default(),
+        )
+        .await
+    };
+    assert!(process(task7.clone()).await.is_err());
+    std::fs::remove_file(&counter).unwrap();
+    /* the `Command`'s own `retry` wins over the `Task`'s */
+    assert!(process(Task {
+        commands: vec![flaky(Some(Retry {
+            count: 2,
+            backoff_ms: 1,
+            retry_on: None,
+        }))],
+        ..task7
+    })
+    .await
+    .is_ok());
+    std::fs::remove_file(&counter).unwrap();
+
+    /* bounds the whole `Task`, not each `Command` */
+    let nap = Command::Exec(ExecCommandArgs {
+        args: ExecArgs {
+            program: String::from("/bin/sh"),
+            args: vec![String::from("-c"), String::from("sleep 0.1")],
+            cwd: None,
+        },
+        ..ExecCommandArgs::default()
+    });
+    match process(Task {
+        commands: vec![nap.clone(), nap.clone(), nap],
+        timeout_ms: Some(200),
+        ..Task::default()
+    })
+    .await
+    {
+        Err(VermanSchemaError::Timeout(200)) => {}
+        other => panic!("expected `Timeout` got {:?}", other),
+    }
+
+    /* synchronous `Command`s time out too */
+    match process(Task {
+        commands: vec![Command::Jaq(CommonContent {
+            content: Some(serde_json::Value::String(String::from(
+                "reduce range(3000000) as $x (0; . + 1)",
+            ))),
+            env: Some(indexmap::indexmap! {
+                CommandKey::PreviousContent.to_string() => serde_json::Value::Null
+            }),
+            timeout_ms: Some(20),
+            ..CommonContent::default()
+        })],
+        ..Task::default()
+    })
+    .await
+    {
+        Err(VermanSchemaError::Timeout(20)) => {}
+        other => panic!("expected `Timeout` got {:?}", other),
+    }
+}
+
+#[test]
+fn test_retry_should_retry() {
+    let retry = Retry {
+        count: 1,
+        backoff_ms: 0,
+        retry_on: Some(vec![502, 503]),
+    };
+    assert!(retry.should_retry(&VermanSchemaError::HttpError(503)));
+    assert!(!retry.should_retry(&VermanSchemaError::HttpErrorWithBody(404, String::new())));
+    assert!(retry.should_retry(&VermanSchemaError::Timeout(1)));
+}
+
 /*
 let cmd0: CommandArgs = serde_json::json!({
     "cmd": "Echo",
@@ -1,4 +1,12 @@
-#[derive(Default, Debug, Clone, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
+#[derive(
+    Default,
+    Debug,
+    Clone,
+    PartialEq,
+    schemars::JsonSchema,
+    serde_derive::Serialize,
+    serde_derive::Deserialize,
+)]
 pub struct Root {
     #[serde(default = "default_name")]
     pub name: std::borrow::Cow<'static, str>,
@@ -19,20 +27,34 @@ pub struct Root {
     pub env_vars: Option<indexmap::IndexMap<String, String>>,
 }
 
-#[derive(Debug, Clone, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    schemars::JsonSchema,
+    serde_derive::Serialize,
+    serde_derive::Deserialize,
+)]
 pub struct VermanConfig {
     /// If shebang is provided, takes priority; otherwise this default is used
     /// Special lines:
     /// - `"#!/echo` simply outputs the lines after shebang
     /// - `"#!/jq"` uses internal `jq` implementation (`jaq`) if feature `jaq` else errs
-    /// - `"#!/deno"` uses internal deno dependency (WASM, js, ts) if feature `js` enabled else errs
-    /// - `"#!/wasm"` uses internal deno dependency (WASM, js, ts) if feature `js` enabled else errs
-    /// - `"#!/js"` uses internal deno dependency (WASM, js, ts) if feature `js` enabled else errs
+    /// - `"#!/deno"` uses internal QuickJS dependency (js) if feature `js` enabled else errs;
+    ///   only JavaScript, TypeScript is out of scope (compile it to JavaScript beforehand)
+    /// - `"#!/wasm"` runs the `.wasm` module at the path after shebang if feature `wasm` enabled else errs
+    /// - `"#!/js"` uses internal QuickJS dependency (js) if feature `js` enabled else errs
     /// - `"#!/lua"` uses internal Lua dependency if feature `lua` enabled else errs
     /// - `"#!/python"` uses internal Python dependency if feature `python` enabled else errs
     shell: String,
 }
 
+impl VermanConfig {
+    pub fn shell(&self) -> &str {
+        self.shell.as_str()
+    }
+}
+
 impl Default for VermanConfig {
     fn default() -> Self {
         Self {
@@ -46,7 +68,15 @@ const fn default_name() -> std::borrow::Cow<'static, str> {
     std::borrow::Cow::Borrowed(NAME)
 }
 
-#[derive(Default, Debug, Clone, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
+#[derive(
+    Default,
+    Debug,
+    Clone,
+    PartialEq,
+    schemars::JsonSchema,
+    serde_derive::Serialize,
+    serde_derive::Deserialize,
+)]
 pub struct StateValues {
     pub kind: Option<String>,
     pub install: Option<State>,
@@ -55,7 +85,15 @@ pub struct StateValues {
     pub stop: Option<State>,
 }
 
-#[derive(Debug, Default, Clone, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    schemars::JsonSchema,
+    serde_derive::Serialize,
+    serde_derive::Deserialize,
+)]
 #[serde(rename_all = "snake_case")]
 pub enum State {
     /// [app/component] install or reinstall
@@ -86,7 +124,15 @@ pub enum State {
     DryRun,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
+#[derive(
+    Default,
+    Debug,
+    Clone,
+    PartialEq,
+    schemars::JsonSchema,
+    serde_derive::Serialize,
+    serde_derive::Deserialize,
+)]
 pub struct ServerConfiguration {
     pub kind: String,
     pub versions: Option<Vec<String>>,
@@ -95,7 +141,15 @@ pub struct ServerConfiguration {
     pub env_vars: Option<indexmap::IndexMap<String, String>>,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
+#[derive(
+    Default,
+    Debug,
+    Clone,
+    PartialEq,
+    schemars::JsonSchema,
+    serde_derive::Serialize,
+    serde_derive::Deserialize,
+)]
 pub struct ProtocolConfiguration {
     /// E.g., "localhost" | "127.0.0.1" | "::1" | "my_name.verman.io"
     pub name: Option<String>,
@@ -109,7 +163,15 @@ pub struct ProtocolConfiguration {
 
 /// URI generalised to UTF8 https://en.wikipedia.org/wiki/Internationalized_Resource_Identifier
 // type Iri = String;
-#[derive(Default, Debug, Clone, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
+#[derive(
+    Default,
+    Debug,
+    Clone,
+    PartialEq,
+    schemars::JsonSchema,
+    serde_derive::Serialize,
+    serde_derive::Deserialize,
+)]
 #[serde(rename_all = "snake_case")]
 pub struct Component {
     pub src_uri: Option<String>,
@@ -120,19 +182,34 @@ pub struct Component {
     pub mounts: Option<Vec<Mount>>,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
+#[derive(
+    Default,
+    Debug,
+    Clone,
+    PartialEq,
+    schemars::JsonSchema,
+    serde_derive::Serialize,
+    serde_derive::Deserialize,
+)]
 #[serde(rename_all = "snake_case")]
 pub struct Mount {
     pub when: String,
     pub uri: Option<String>,
     pub src_uri: Option<String>,
     pub action: String,
-    // Future: enable an IDL (like JSON-schema) to validate these args
-    // Future: allow this IDL to be provided by URI
+    /// Validated against the JSON Schema of `action`, see `stack::mount_action::MountActionRegistry`
     pub action_args: Option<serde_json::Value>,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
+#[derive(
+    Default,
+    Debug,
+    Clone,
+    PartialEq,
+    schemars::JsonSchema,
+    serde_derive::Serialize,
+    serde_derive::Deserialize,
+)]
 #[serde(rename_all = "snake_case")]
 pub struct Constraint {
     pub kind: String,
@@ -142,7 +219,15 @@ pub struct Constraint {
 
 /// OSs from https://github.com/rust-lang/rust/blob/1.77.0/library/std/src/env.rs#L947-L961
 #[derive(
-    Debug, Default, Clone, PartialEq, Eq, Hash, serde_derive::Serialize, serde_derive::Deserialize,
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    schemars::JsonSchema,
+    serde_derive::Serialize,
+    serde_derive::Deserialize,
 )]
 #[serde(rename_all = "lowercase")]
 pub enum Os {
@@ -161,7 +246,15 @@ pub enum Os {
     Unspecified,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
+#[derive(
+    Default,
+    Debug,
+    Clone,
+    PartialEq,
+    schemars::JsonSchema,
+    serde_derive::Serialize,
+    serde_derive::Deserialize,
+)]
 pub struct VendorVersion {
     pub vendor: Option<String>,
     pub version: Option<String>,